@@ -0,0 +1,59 @@
+//! Uses parse-helper from a `no_std` crate. This only has to build, for a target without std.
+#![no_std]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use parse_helper::{Char, Le, NestingTooDeep, ParseHelper};
+
+/// Sums a comma separated list of numbers, without allocating.
+pub fn sum(input: &str) -> Option<u32> {
+    let mut ph = ParseHelper::new_char_oriented(input);
+    let mut total = 0u32;
+
+    loop {
+        ph.accept_zero_or_more_whitespace();
+        let digits = ph.accept_until_char_with(|c| !c.is_ascii_digit());
+        total = total.checked_add(digits.parse().ok()?)?;
+
+        ph.accept_zero_or_more_whitespace();
+        if ph.accept_char(',').is_none() {
+            return ph.done().then_some(total);
+        }
+    }
+}
+
+/// Reads a little endian length prefixed payload.
+pub fn payload(input: &[u8]) -> Option<&[u8]> {
+    ParseHelper::new_byte_oriented(input).accept_length_prefixed::<u16, Le>()
+}
+
+/// Counts how deeply brackets are nested.
+pub fn depth(ph: &mut ParseHelper<str, Char>) -> Result<usize, NestingTooDeep<Char>> {
+    let mut deepest = 0;
+    while ph.accept_char('[').is_some() {
+        deepest = deepest.max(ph.nested(depth)?? + 1);
+        ph.accept_char(']');
+    }
+    Ok(deepest)
+}
+
+/// Checks that the input is JSON, without allocating.
+pub fn is_json(input: &str) -> bool {
+    ParseHelper::new_char_oriented(input)
+        .validate_json()
+        .is_ok()
+}
+
+/// Collects the words of a sentence.
+#[cfg(feature = "alloc")]
+pub fn words(input: &str) -> alloc::vec::Vec<&str> {
+    let mut ph = ParseHelper::new_char_oriented(input);
+    let mut words = alloc::vec::Vec::new();
+
+    while !ph.done() {
+        ph.accept_zero_or_more_whitespace();
+        words.push(ph.accept_until_whitespace());
+    }
+    words
+}
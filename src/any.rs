@@ -1,14 +1,17 @@
-use core::mem;
-use std::{
+use core::{
+    fmt::{self, Display, Formatter},
     marker::PhantomData,
+    mem,
     ops::{Index, Range},
+    slice::SliceIndex,
 };
 
-use crate::{Byte, ParseHelper};
+use crate::{Byte, Char, ParseHelper};
 
-mod private {
-    use core::ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
-    use std::ops::Index;
+pub(crate) mod private {
+    use core::ops::{
+        Index, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive,
+    };
 
     use super::Mark;
     use crate::Byte;
@@ -16,9 +19,17 @@ mod private {
     pub trait SliceRange<'a, B, T: ?Sized> {
         type RangeTy;
 
+        /// The range of byte positions this range of marks covers.
+        fn range(&self) -> Self::RangeTy;
+
+        // indexing with marks panics just like indexing with numbers does
+        #[allow(clippy::indexing_slicing)]
         fn slice(&self, inp: &'a T) -> &'a <T as Index<Self::RangeTy>>::Output
         where
-            T: Index<Self::RangeTy>;
+            T: Index<Self::RangeTy>,
+        {
+            &inp[self.range()]
+        }
     }
 
     // NOTE: the items for which this trait is implemented are carefully
@@ -27,58 +38,44 @@ mod private {
     impl<'a, B, T: ?Sized> SliceRange<'a, B, T> for Range<Mark<B>> {
         type RangeTy = Range<usize>;
 
-        fn slice(&self, inp: &'a T) -> &'a <T as Index<Self::RangeTy>>::Output
-        where
-            T: Index<Self::RangeTy>,
-        {
-            &inp[self.start.byte_position..self.end.byte_position]
+        fn range(&self) -> Self::RangeTy {
+            self.start.byte_position..self.end.byte_position
         }
     }
     impl<'a, B, T: ?Sized> SliceRange<'a, B, T> for RangeFrom<Mark<B>> {
         type RangeTy = RangeFrom<usize>;
-        fn slice(&self, inp: &'a T) -> &'a <T as Index<Self::RangeTy>>::Output
-        where
-            T: Index<Self::RangeTy>,
-        {
-            &inp[self.start.byte_position..]
+
+        fn range(&self) -> Self::RangeTy {
+            self.start.byte_position..
         }
     }
     impl<'a, B, T: ?Sized> SliceRange<'a, B, T> for RangeFull {
         type RangeTy = RangeFull;
-        fn slice(&self, inp: &'a T) -> &'a <T as Index<Self::RangeTy>>::Output
-        where
-            T: Index<Self::RangeTy>,
-        {
-            &inp[..]
+
+        fn range(&self) -> Self::RangeTy {
+            ..
         }
     }
     impl<'a, B, T: ?Sized> SliceRange<'a, B, T> for RangeTo<Mark<B>> {
         type RangeTy = RangeTo<usize>;
-        fn slice(&self, inp: &'a T) -> &'a <T as Index<Self::RangeTy>>::Output
-        where
-            T: Index<Self::RangeTy>,
-        {
-            &inp[..self.end.byte_position]
+
+        fn range(&self) -> Self::RangeTy {
+            ..self.end.byte_position
         }
     }
 
     impl<'a, T: ?Sized> SliceRange<'a, Byte, T> for RangeToInclusive<Mark<Byte>> {
         type RangeTy = RangeToInclusive<usize>;
-        fn slice(&self, inp: &'a T) -> &'a <T as Index<Self::RangeTy>>::Output
-        where
-            T: Index<Self::RangeTy>,
-        {
-            &inp[..=self.end.byte_position]
+
+        fn range(&self) -> Self::RangeTy {
+            ..=self.end.byte_position
         }
     }
     impl<'a, T: ?Sized> SliceRange<'a, Byte, T> for RangeInclusive<Mark<Byte>> {
         type RangeTy = RangeInclusive<usize>;
 
-        fn slice(&self, inp: &'a T) -> &'a <T as Index<Self::RangeTy>>::Output
-        where
-            T: Index<Self::RangeTy>,
-        {
-            &inp[self.start().byte_position..=self.end().byte_position]
+        fn range(&self) -> Self::RangeTy {
+            self.start().byte_position..=self.end().byte_position
         }
     }
 }
@@ -92,12 +89,48 @@ pub struct Mark<B> {
 }
 
 impl<B> Mark<B> {
+    pub(crate) const fn new(byte_position: usize) -> Self {
+        Self {
+            byte_position,
+            boundary: PhantomData,
+        }
+    }
+
     /// get the position in the input of this mark.
-    pub fn byte_position(&self) -> usize {
+    pub const fn byte_position(&self) -> usize {
         self.byte_position
     }
 }
 
+/// Error returned by the non-panicking (`try_*`) counterparts of methods that panic when they
+/// would end up outside of the input.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct OutOfBounds;
+
+impl Display for OutOfBounds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "out of bounds of the input")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OutOfBounds {}
+
+// marks serialize as just their byte position
+#[cfg(feature = "serde")]
+impl<B> serde::Serialize for Mark<B> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.byte_position.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, B> serde::Deserialize<'de> for Mark<B> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        usize::deserialize(deserializer).map(Self::new)
+    }
+}
+
 impl<'a, T: ?Sized, B> ParseHelper<'a, T, B> {
     /// Same as clone, but this can help show intent (together with
     /// [`restore_backup`](Self::restore_backup))
@@ -110,10 +143,15 @@ impl<'a, T: ?Sized, B> ParseHelper<'a, T, B> {
         *self = other;
     }
 
+    // moves forward over `n` bytes that were checked to be in the input, which can't overflow
+    pub(crate) const fn advance(&mut self, n: usize) {
+        self.byte_position = self.byte_position.saturating_add(n);
+    }
+
     /// Creates a mark at the current position of the parse helper.
     ///
     /// Used in combination with [`slice`](Self::slice)
-    pub fn mark(&self) -> Mark<B> {
+    pub const fn mark(&self) -> Mark<B> {
         Mark {
             byte_position: self.byte_position,
             boundary: PhantomData,
@@ -140,6 +178,12 @@ impl<'a, T: ?Sized, B> ParseHelper<'a, T, B> {
     /// Note that you can slice a [`Char`](crate::Char) oriented parse helper only using exclusive ranges
     /// (to not split utf8 codepoints accidentally), but [`Byte`] orieinted parse helpers can
     /// be slices using these ranges. This property is encoded in a sealed trait called `SliceRange`.
+    ///
+    /// # Panics
+    ///
+    /// When the range is out of bounds of the input, or the start of the range lies after the end
+    /// (this can happen when using marks of another parse helper).
+    /// See [`try_slice`](Self::try_slice) for a version that doesn't panic.
     pub fn slice<R>(&self, range: R) -> &'a <T as Index<R::RangeTy>>::Output
     where
         R: private::SliceRange<'a, B, T>,
@@ -213,7 +257,11 @@ impl<'a, T: ?Sized, B> ParseHelper<'a, T, B> {
         T: Index<Range<usize>>,
     {
         let old = self.create_backup();
-        let (accepted, slice) = self.slice_accepted(closure);
+        let (accepted, slice) = self.traced_scope(
+            "slice_accepted_option",
+            |ph| ph.slice_accepted(closure),
+            |(accepted, _)| accepted,
+        );
 
         match accepted {
             Some(_) => Some(slice),
@@ -225,6 +273,64 @@ impl<'a, T: ?Sized, B> ParseHelper<'a, T, B> {
     }
 }
 
+impl<'a, T: ?Sized> ParseHelper<'a, T, Char>
+where
+    T: AsRef<str>,
+{
+    /// Like [`slice`](Self::slice), but returns an error instead of panicking when the range is
+    /// out of bounds, doesn't lie on utf8 boundaries or its start lies after its end.
+    ///
+    /// ```
+    /// use parse_helper::{OutOfBounds, ParseHelper};
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("ab cd");
+    /// let start = ph.mark();
+    /// ph.accept_until_whitespace();
+    /// let end = ph.mark();
+    ///
+    /// assert_eq!(ph.try_slice(start..end), Ok("ab"));
+    /// assert_eq!(ph.try_slice(end..start), Err(OutOfBounds));
+    /// ```
+    pub fn try_slice<R>(&self, range: R) -> Result<&'a str, OutOfBounds>
+    where
+        R: private::SliceRange<'a, Char, T>,
+        R::RangeTy: SliceIndex<str, Output = str>,
+    {
+        AsRef::<str>::as_ref(self.input)
+            .get(range.range())
+            .ok_or(OutOfBounds)
+    }
+}
+
+impl<'a, T: ?Sized> ParseHelper<'a, T, Byte>
+where
+    T: AsRef<[u8]>,
+{
+    /// Like [`slice`](Self::slice), but returns an error instead of panicking when the range is
+    /// out of bounds or its start lies after its end.
+    ///
+    /// Note that this always returns a byte slice, even if the input is a string.
+    ///
+    /// ```
+    /// use parse_helper::{OutOfBounds, ParseHelper};
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented("héllo");
+    /// let start = ph.mark();
+    /// ph.skip_bytes(2);
+    /// let end = ph.mark();
+    ///
+    /// assert_eq!(ph.try_slice(start..end), Ok(b"h\xc3".as_slice()));
+    /// assert_eq!(ph.try_slice(end..start), Err(OutOfBounds));
+    /// ```
+    pub fn try_slice<R>(&self, range: R) -> Result<&'a [u8], OutOfBounds>
+    where
+        R: private::SliceRange<'a, Byte, T>,
+        R::RangeTy: SliceIndex<[u8], Output = [u8]>,
+    {
+        self.input.as_ref().get(range.range()).ok_or(OutOfBounds)
+    }
+}
+
 impl<'a, T: ?Sized, B> ParseHelper<'a, T, B>
 where
     T: AsRef<[u8]>,
@@ -237,13 +343,13 @@ where
     /// Returns how many bytes have been accepted sofar.
     /// This is equivalent to getting the current "byte position", the counter that internally
     /// keeps track of accepts
-    pub fn bytes_accepted(&self) -> usize {
+    pub const fn bytes_accepted(&self) -> usize {
         self.byte_position
     }
 
     /// Returns how many bytes are left to parse
     pub fn bytes_left(&self) -> usize {
-        self.as_ref().len() - self.byte_position
+        self.as_ref().len().saturating_sub(self.byte_position)
     }
 
     /// returns the next byte that is going to be parsed.
@@ -0,0 +1,503 @@
+use core::ffi::CStr;
+
+use crate::{
+    trace::{ByteLiteral, NoArgument},
+    Byte, Mark, ParseHelper,
+};
+
+mod private {
+    pub trait ByteOrder {
+        /// Decodes at most 8 bytes into an integer.
+        fn decode(bytes: &[u8]) -> u64;
+    }
+
+    pub trait LengthPrefix {
+        /// The number of bytes the length is encoded in
+        const SIZE: usize;
+    }
+
+    impl LengthPrefix for u8 {
+        const SIZE: usize = 1;
+    }
+    impl LengthPrefix for u16 {
+        const SIZE: usize = 2;
+    }
+    impl LengthPrefix for u32 {
+        const SIZE: usize = 4;
+    }
+    impl LengthPrefix for u64 {
+        const SIZE: usize = 8;
+    }
+}
+
+/// Little endian byte order, the least significant byte comes first.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Le;
+impl private::ByteOrder for Le {
+    fn decode(bytes: &[u8]) -> u64 {
+        bytes
+            .iter()
+            .rev()
+            .fold(0, |acc, &b| (acc << 8) | u64::from(b))
+    }
+}
+
+/// Big endian byte order, the most significant byte comes first.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Be;
+impl private::ByteOrder for Be {
+    fn decode(bytes: &[u8]) -> u64 {
+        bytes.iter().fold(0, |acc, &b| (acc << 8) | u64::from(b))
+    }
+}
+
+/// The native byte order of the target.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Ne;
+impl private::ByteOrder for Ne {
+    fn decode(bytes: &[u8]) -> u64 {
+        if cfg!(target_endian = "little") {
+            Le::decode(bytes)
+        } else {
+            Be::decode(bytes)
+        }
+    }
+}
+
+// generates `accept_*` methods for a fixed width number type,
+// one for every byte order it can be encoded in.
+macro_rules! accept_number {
+    ($($ty: ident: $le: ident, $be: ident, $ne: ident;)*) => {
+        $(
+            #[doc = concat!("Accepts a little endian encoded `", stringify!($ty), "`.")]
+            ///
+            /// Returns `None` without accepting anything when there are not enough bytes left.
+            pub fn $le(&mut self) -> Option<$ty> {
+                self.traced(stringify!($le), &NoArgument, |ph| {
+                    ph.accept_array().map(|x| $ty::from_le_bytes(*x))
+                })
+            }
+
+            #[doc = concat!("Accepts a big endian encoded `", stringify!($ty), "`.")]
+            ///
+            /// Returns `None` without accepting anything when there are not enough bytes left.
+            pub fn $be(&mut self) -> Option<$ty> {
+                self.traced(stringify!($be), &NoArgument, |ph| {
+                    ph.accept_array().map(|x| $ty::from_be_bytes(*x))
+                })
+            }
+
+            #[doc = concat!("Accepts a `", stringify!($ty), "` encoded in the native byte order of the target.")]
+            ///
+            /// Returns `None` without accepting anything when there are not enough bytes left.
+            pub fn $ne(&mut self) -> Option<$ty> {
+                self.traced(stringify!($ne), &NoArgument, |ph| {
+                    ph.accept_array().map(|x| $ty::from_ne_bytes(*x))
+                })
+            }
+        )*
+    };
+}
+
+impl<'a, T: ?Sized> ParseHelper<'a, T, Byte>
+where
+    T: AsRef<[u8]>,
+{
+    /// Accepts exactly `N` bytes, and returns them as an array reference.
+    ///
+    /// Returns `None` without accepting anything when there are less than `N` bytes left.
+    ///
+    /// ```rust
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented(b"\x7fELF\x02");
+    ///
+    /// assert_eq!(ph.accept_array::<4>(), Some(b"\x7fELF"));
+    /// assert_eq!(ph.accept_array::<2>(), None);
+    /// assert_eq!(ph.accept_array::<1>(), Some(b"\x02"));
+    /// ```
+    pub fn accept_array<const N: usize>(&mut self) -> Option<&'a [u8; N]> {
+        self.traced("accept_array", &N, |ph| {
+            let res = ph.leftover().get(..N)?.try_into().ok()?;
+            ph.advance(N);
+
+            Some(res)
+        })
+    }
+
+    /// Accepts exactly `n` bytes.
+    ///
+    /// Returns `None` without accepting anything when there are less than `n` bytes left.
+    /// Unlike [`skip_bytes`](Self::skip_bytes), this never panics.
+    pub fn accept_n_bytes(&mut self, n: usize) -> Option<&'a [u8]> {
+        self.traced("accept_n_bytes", &n, |ph| {
+            let res = ph.leftover().get(..n)?;
+            ph.advance(n);
+
+            Some(res)
+        })
+    }
+
+    /// Accepts a length `L` (one of `u8`, `u16`, `u32` or `u64`) encoded in byte order `E`
+    /// (one of [`Le`], [`Be`] or [`Ne`]), followed by that many bytes.
+    ///
+    /// Returns the bytes after the length, or `None` without accepting anything
+    /// if there are not enough bytes left.
+    ///
+    /// ```rust
+    /// use parse_helper::{Be, Le, ParseHelper};
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented(b"\x03\x00abc\x00\x05de");
+    ///
+    /// assert_eq!(ph.accept_length_prefixed::<u16, Le>(), Some(b"abc".as_slice()));
+    /// assert_eq!(ph.accept_length_prefixed::<u16, Be>(), None);
+    /// assert_eq!(ph.leftover(), b"\x00\x05de");
+    /// ```
+    pub fn accept_length_prefixed<L, E>(&mut self) -> Option<&'a [u8]>
+    where
+        L: private::LengthPrefix,
+        E: private::ByteOrder,
+    {
+        self.traced("accept_length_prefixed", &NoArgument, |ph| {
+            let old = ph.create_backup();
+
+            let len = E::decode(ph.accept_n_bytes(L::SIZE)?);
+            let res = usize::try_from(len)
+                .ok()
+                .and_then(|len| ph.accept_n_bytes(len));
+
+            if res.is_none() {
+                ph.restore_backup(old);
+            }
+            res
+        })
+    }
+
+    /// Accepts a nul terminated C string, including the nul byte.
+    ///
+    /// Returns `None` without accepting anything when there is no nul byte in the rest
+    /// of the input.
+    ///
+    /// ```rust
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented(b"hello\0world");
+    ///
+    /// assert_eq!(ph.accept_cstr(), Some(c"hello"));
+    /// assert_eq!(ph.accept_cstr(), None);
+    /// assert_eq!(ph.leftover(), b"world");
+    /// ```
+    pub fn accept_cstr(&mut self) -> Option<&'a CStr> {
+        self.traced("accept_cstr", &NoArgument, |ph| {
+            let res = CStr::from_bytes_until_nul(ph.leftover()).ok()?;
+            ph.advance(res.to_bytes_with_nul().len());
+
+            Some(res)
+        })
+    }
+
+    /// Accepts exactly `n` bytes, and returns a new parse helper that parses only those bytes.
+    /// This makes sure that whatever parses the inner part can never read past the end of it.
+    ///
+    /// Note that [`Mark`](crate::Mark)s of the returned parse helper are relative to the start of
+    /// the `n` bytes, and can't be used with this parse helper.
+    ///
+    /// The returned parse helper continues at the nesting [`depth`](Self::depth) of this one, with
+    /// the same limit. It doesn't record in the tracer of this one, since the positions it would
+    /// record there are relative to the start of the `n` bytes too.
+    ///
+    /// Returns `None` without accepting anything when there are less than `n` bytes left.
+    ///
+    /// ```rust
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented(b"\x02\x01\x02\x03");
+    ///
+    /// let len = ph.accept_u8().unwrap();
+    /// let mut inner = ph.sub_parser(len.into()).unwrap();
+    ///
+    /// assert_eq!(inner.accept_u16_le(), Some(0x0201));
+    /// assert_eq!(inner.accept_u8(), None);
+    /// assert_eq!(ph.accept_u8(), Some(3));
+    /// ```
+    pub fn sub_parser(&mut self, n: usize) -> Option<ParseHelper<'a, [u8], Byte>> {
+        let mut sub = ParseHelper::new_byte_oriented(self.accept_n_bytes(n)?);
+        sub.depth = self.depth;
+        sub.max_depth = self.max_depth;
+        Some(sub)
+    }
+
+    /// Returns `true` if the current position is a multiple of `n`.
+    ///
+    /// Nothing is aligned to `0`, so this always returns `false` when `n` is `0`.
+    pub fn position_is_aligned(&self, n: usize) -> bool {
+        self.byte_position.checked_rem(n) == Some(0)
+    }
+
+    /// Skips bytes until the current position is a multiple of `n`, and returns what was skipped.
+    ///
+    /// Returns `None` without accepting anything when the input ends before the next aligned
+    /// position, or when `n` is `0`.
+    ///
+    /// ```rust
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented(b"abcdefg");
+    /// ph.skip_byte();
+    ///
+    /// assert_eq!(ph.align_to(4), Some(b"bcd".as_slice()));
+    /// assert!(ph.position_is_aligned(4));
+    /// assert_eq!(ph.align_to(4), Some(b"".as_slice()));
+    ///
+    /// ph.skip_byte();
+    /// assert_eq!(ph.align_to(8), None);
+    /// assert_eq!(ph.leftover(), b"fg");
+    /// ```
+    pub fn align_to(&mut self, n: usize) -> Option<&'a [u8]> {
+        self.align_to_relative(Mark::new(0), n)
+    }
+
+    /// Like [`align_to`](Self::align_to), but aligns relative to `base` instead of the start
+    /// of the input. Useful when a format aligns to offsets within a section.
+    ///
+    /// Returns `None` without accepting anything when the input ends before the next aligned
+    /// position, when `n` is `0`, or when `base` lies after the current position.
+    ///
+    /// ```rust
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented(b"xxabcdef");
+    /// ph.skip_bytes(2);
+    /// let base = ph.mark();
+    ///
+    /// ph.skip_byte();
+    /// assert_eq!(ph.align_to_relative(base, 4), Some(b"bcd".as_slice()));
+    /// assert_eq!(ph.leftover(), b"ef");
+    /// ```
+    pub fn align_to_relative(&mut self, base: Mark<Byte>, n: usize) -> Option<&'a [u8]> {
+        let offset = self.byte_position.checked_sub(base.byte_position())?;
+        let padding = match offset.checked_rem(n)? {
+            0 => 0,
+            rem => n.saturating_sub(rem),
+        };
+
+        self.accept_n_bytes(padding)
+    }
+
+    /// Like [`align_to`](Self::align_to), but only succeeds when every skipped byte equals
+    /// `byte`. Most formats pad with zeroes, so `byte` is usually `0`.
+    ///
+    /// Returns `None` without accepting anything when a padding byte has a different value,
+    /// when the input ends before the next aligned position, or when `n` is `0`.
+    ///
+    /// ```rust
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented(b"a\0\0\0b\0\x01\0");
+    /// ph.skip_byte();
+    /// assert_eq!(ph.accept_padding(0, 4), Some(b"\0\0\0".as_slice()));
+    ///
+    /// ph.skip_byte();
+    /// assert_eq!(ph.accept_padding(0, 4), None);
+    /// assert_eq!(ph.leftover(), b"\0\x01\0");
+    /// ```
+    pub fn accept_padding(&mut self, byte: u8, n: usize) -> Option<&'a [u8]> {
+        self.traced("accept_padding", &(ByteLiteral(byte), n), |ph| {
+            let old = ph.create_backup();
+
+            let res = ph
+                .align_to(n)
+                .filter(|padding| padding.iter().all(|&b| b == byte));
+
+            if res.is_none() {
+                ph.restore_backup(old);
+            }
+            res
+        })
+    }
+
+    /// Accepts a single byte, whatever its value is.
+    pub fn accept_u8(&mut self) -> Option<u8> {
+        self.traced("accept_u8", &NoArgument, |ph| ph.accept_byte_with(|_| true))
+    }
+
+    /// Accepts a single byte, and interprets it as a two's complement signed number.
+    pub fn accept_i8(&mut self) -> Option<i8> {
+        self.traced("accept_i8", &NoArgument, |ph| {
+            ph.accept_array().map(|x| i8::from_ne_bytes(*x))
+        })
+    }
+
+    accept_number! {
+        u16: accept_u16_le, accept_u16_be, accept_u16_ne;
+        u32: accept_u32_le, accept_u32_be, accept_u32_ne;
+        u64: accept_u64_le, accept_u64_be, accept_u64_ne;
+        u128: accept_u128_le, accept_u128_be, accept_u128_ne;
+        i16: accept_i16_le, accept_i16_be, accept_i16_ne;
+        i32: accept_i32_le, accept_i32_be, accept_i32_ne;
+        i64: accept_i64_le, accept_i64_be, accept_i64_ne;
+        i128: accept_i128_le, accept_i128_be, accept_i128_ne;
+        f32: accept_f32_le, accept_f32_be, accept_f32_ne;
+        f64: accept_f64_le, accept_f64_be, accept_f64_ne;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Be, Le, Mark, Ne, ParseHelper};
+
+    // calls every method that shouldn't panic at, and with marks past, the end of the input.
+    #[test]
+    fn no_panics_at_end_of_input() {
+        for input in ["", "a", "\u{e9}", "a\u{1f980}"] {
+            let mut ph = ParseHelper::new_byte_oriented(input);
+            ph.skip_bytes(input.len());
+            let past_end = Mark::new(input.len() + 1);
+
+            assert_eq!(ph.accept_array::<1>(), None);
+            assert_eq!(ph.accept_n_bytes(1), None);
+            assert_eq!(ph.accept_length_prefixed::<u8, Le>(), None);
+            assert_eq!(ph.accept_cstr(), None);
+            assert!(ph.sub_parser(1).is_none());
+            let _ = ph.align_to(usize::MAX);
+            assert_eq!(ph.align_to(0), None);
+            assert_eq!(ph.align_to_relative(past_end, 2), None);
+            let _ = ph.accept_padding(0, 7);
+            assert_eq!(ph.accept_u8(), None);
+            assert_eq!(ph.accept_i8(), None);
+            assert_eq!(ph.accept_u64_le(), None);
+            assert_eq!(ph.accept_f64_be(), None);
+            assert!(ph.done());
+        }
+    }
+
+    #[test]
+    fn accept_integers() {
+        let mut ph = ParseHelper::new_byte_oriented(b"\x01\x02\x03\x04\xff\x01\x02\xfe\xff");
+
+        assert_eq!(ph.accept_u16_le(), Some(0x0201));
+        assert_eq!(ph.accept_u16_be(), Some(0x0304));
+        assert_eq!(ph.accept_i8(), Some(-1));
+        assert_eq!(ph.accept_u8(), Some(1));
+        assert_eq!(ph.accept_u32_le(), None);
+        assert_eq!(ph.bytes_left(), 3);
+        assert_eq!(ph.accept_i16_be(), Some(0x02fe));
+        assert_eq!(ph.accept_i16_le(), None);
+        assert_eq!(ph.accept_i8(), Some(-1));
+        assert_eq!(ph.accept_u8(), None);
+        assert!(ph.done());
+    }
+
+    #[test]
+    fn accept_length_prefixed() {
+        let mut ph = ParseHelper::new_byte_oriented(b"\x02ab\x00\x00\x00\x01c\x05de");
+
+        assert_eq!(
+            ph.accept_length_prefixed::<u8, Ne>(),
+            Some(b"ab".as_slice())
+        );
+        assert_eq!(
+            ph.accept_length_prefixed::<u32, Be>(),
+            Some(b"c".as_slice())
+        );
+        assert_eq!(ph.accept_length_prefixed::<u8, Le>(), None);
+        assert_eq!(ph.accept_length_prefixed::<u64, Le>(), None);
+        assert_eq!(ph.bytes_left(), 3);
+
+        assert_eq!(ph.accept_n_bytes(4), None);
+        assert_eq!(ph.accept_n_bytes(3), Some(b"\x05de".as_slice()));
+        assert_eq!(ph.accept_n_bytes(0), Some(b"".as_slice()));
+    }
+
+    #[test]
+    fn sub_parser_is_bounded() {
+        let mut ph = ParseHelper::new_byte_oriented(b"abcdef");
+        ph.skip_byte();
+
+        let mut inner = ph.sub_parser(3).unwrap();
+        assert_eq!(inner.bytes_left(), 3);
+        assert_eq!(inner.accept("bcde"), None);
+        assert_eq!(inner.accept("bcd"), Some(b"bcd".as_slice()));
+        assert!(inner.done());
+
+        assert!(ph.sub_parser(3).is_none());
+        assert_eq!(ph.leftover(), b"ef");
+    }
+
+    #[test]
+    fn sub_parser_keeps_settings() {
+        let mut ph = ParseHelper::new_byte_oriented(b"abcdef");
+        ph.set_max_depth(2);
+
+        ph.nested(|ph| {
+            let mut inner = ph.sub_parser(3).unwrap();
+            assert_eq!(inner.depth(), 1);
+            assert_eq!(inner.max_depth(), 2);
+            assert!(inner.nested(|inner| inner.nested(|_| ())).unwrap().is_err());
+        })
+        .unwrap();
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn sub_parser_has_its_own_positions() {
+        let tracer = crate::Tracer::new();
+        let mut ph = ParseHelper::new_byte_oriented(b"\x01\x02\x03\x04");
+        ph.record_trace(&tracer);
+
+        ph.accept_u8();
+        let mut inner = ph.sub_parser(2).unwrap();
+        assert_eq!(inner.mark().byte_position(), 0);
+        assert_eq!(inner.accept_u16_le(), Some(0x0302));
+        assert!(inner.done());
+        assert_eq!(ph.accept_u8(), Some(4));
+
+        tracer.assert_trace(
+            r#"
+            accept_u8() 0..1 ok
+            accept_n_bytes(2) 1..3 ok
+            accept_u8() 3..4 ok
+            "#,
+        );
+    }
+
+    #[test]
+    fn align() {
+        let mut ph = ParseHelper::new_byte_oriented(b"abc");
+        assert!(ph.position_is_aligned(1));
+        assert!(ph.position_is_aligned(4));
+        assert!(!ph.position_is_aligned(0));
+        assert_eq!(ph.align_to(0), None);
+
+        ph.skip_byte();
+        assert!(!ph.position_is_aligned(2));
+        assert_eq!(ph.align_to(2), Some(b"b".as_slice()));
+        // aligning to the very end of the input is fine
+        assert_eq!(ph.align_to(3), Some(b"c".as_slice()));
+        assert!(ph.done());
+        assert_eq!(ph.align_to(3), Some(b"".as_slice()));
+
+        let mut ph = ParseHelper::new_byte_oriented(b"abc");
+        let base = ph.mark();
+        let mut ahead = ph.clone();
+        ahead.skip_byte();
+        let later = ahead.mark();
+
+        assert_eq!(ph.align_to_relative(later, 2), None);
+        assert_eq!(ph.align_to_relative(base, 2), Some(b"".as_slice()));
+    }
+
+    #[test]
+    fn accept_floats() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&1.5f32.to_le_bytes());
+        bytes.extend_from_slice(&(-2.25f64).to_be_bytes());
+        bytes.extend_from_slice(&3.0f64.to_ne_bytes());
+
+        let mut ph = ParseHelper::new_byte_oriented(&bytes);
+        assert_eq!(ph.accept_f32_le(), Some(1.5));
+        assert_eq!(ph.accept_f64_be(), Some(-2.25));
+        assert_eq!(ph.accept_f64_ne(), Some(3.0));
+        assert_eq!(ph.accept_f32_ne(), None);
+    }
+}
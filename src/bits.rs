@@ -0,0 +1,373 @@
+use core::marker::PhantomData;
+
+use crate::{Byte, Mark, ParseHelper};
+
+mod private {
+    pub trait BitOrder {
+        /// Returns the bit at `offset` (counted in reading order) of `byte`.
+        fn bit(byte: u8, offset: u32) -> bool;
+
+        /// Adds the `index`th bit that was read to the value read so far.
+        fn push(acc: u64, index: u32, bit: bool) -> u64;
+    }
+}
+
+/// Reads the bits in a byte from most significant to least significant,
+/// and builds values with the first bit read as the most significant bit.
+///
+/// This is the order most video codecs (like H.264) use.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Msb0;
+impl private::BitOrder for Msb0 {
+    fn bit(byte: u8, offset: u32) -> bool {
+        byte & (0x80 >> offset) != 0
+    }
+
+    fn push(acc: u64, _index: u32, bit: bool) -> u64 {
+        (acc << 1) | u64::from(bit)
+    }
+}
+
+/// Reads the bits in a byte from least significant to most significant,
+/// and builds values with the first bit read as the least significant bit.
+///
+/// This is the order DEFLATE uses.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Lsb0;
+impl private::BitOrder for Lsb0 {
+    fn bit(byte: u8, offset: u32) -> bool {
+        byte & (1 << offset) != 0
+    }
+
+    fn push(acc: u64, index: u32, bit: bool) -> u64 {
+        acc | (u64::from(bit) << index)
+    }
+}
+
+/// Marks a position in an input stream with bit granularity.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct BitMark {
+    mark: Mark<Byte>,
+    bit_offset: u32,
+}
+
+impl BitMark {
+    /// The byte the position is in.
+    pub fn mark(&self) -> Mark<Byte> {
+        self.mark
+    }
+
+    /// How many bits of the byte at [`mark`](Self::mark) come before the position (`0..8`).
+    pub fn bit_offset(&self) -> u32 {
+        self.bit_offset
+    }
+
+    /// get the position in the input of this mark, in bits.
+    pub fn bit_position(&self) -> usize {
+        self.mark
+            .byte_position()
+            .saturating_mul(8)
+            .saturating_add(self.bit_offset as usize)
+    }
+}
+
+/// A view on a byte oriented [`ParseHelper`] that can read input one bit at a time.
+///
+/// Created using [`ParseHelper::into_bit_reader`]. The order `O` ([`Msb0`] or [`Lsb0`])
+/// determines in which order bits are read from every byte.
+///
+/// ```rust
+/// use parse_helper::{Msb0, ParseHelper};
+///
+/// let ph = ParseHelper::new_byte_oriented(&[0b1011_0010, 0xff]);
+/// let mut bits = ph.into_bit_reader::<Msb0>();
+///
+/// assert_eq!(bits.read_bit(), Some(true));
+/// assert_eq!(bits.read_bits(3), Some(0b011));
+///
+/// let mut ph = bits.into_byte_oriented();
+/// assert_eq!(ph.accept_u8(), Some(0xff));
+/// ```
+pub struct BitReader<'a, T: ?Sized, O> {
+    inner: ParseHelper<'a, T, Byte>,
+    // how many bits of the byte at `inner.byte_position` were already read, always less than 8
+    bit_offset: u32,
+    order: PhantomData<O>,
+}
+
+impl<'a, T: ?Sized, O> Clone for BitReader<'a, T, O> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            bit_offset: self.bit_offset,
+            order: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: ?Sized> ParseHelper<'a, T, Byte>
+where
+    T: AsRef<[u8]>,
+{
+    /// Turns this parse helper into a [`BitReader`] which starts reading at the current byte.
+    pub fn into_bit_reader<O: private::BitOrder>(self) -> BitReader<'a, T, O> {
+        BitReader {
+            inner: self,
+            bit_offset: 0,
+            order: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: ?Sized, O> BitReader<'a, T, O>
+where
+    T: AsRef<[u8]>,
+    O: private::BitOrder,
+{
+    /// Turns this bit reader back into a byte oriented parse helper.
+    ///
+    /// If the bit reader is in the middle of a byte, the rest of that byte is skipped.
+    pub fn into_byte_oriented(mut self) -> ParseHelper<'a, T, Byte> {
+        self.align_to_byte();
+        self.inner
+    }
+
+    /// Same as clone, but this can help show intent (together with
+    /// [`restore_backup`](Self::restore_backup))
+    pub fn create_backup(&self) -> Self {
+        self.clone()
+    }
+
+    /// simply overwrites self. However, can be nice to show intent.
+    pub fn restore_backup(&mut self, other: Self) {
+        *self = other;
+    }
+
+    /// Creates a mark at the current bit position of the reader.
+    pub fn bit_mark(&self) -> BitMark {
+        BitMark {
+            mark: self.inner.mark(),
+            bit_offset: self.bit_offset,
+        }
+    }
+
+    /// Returns how many bits are left to read
+    pub fn bits_left(&self) -> usize {
+        self.inner
+            .bytes_left()
+            .saturating_mul(8)
+            .saturating_sub(self.bit_offset as usize)
+    }
+
+    /// Returns `true` if the reader is at the start of a byte.
+    pub fn is_byte_aligned(&self) -> bool {
+        self.bit_offset == 0
+    }
+
+    /// Skips the rest of the current byte, unless the reader is already at the start of a byte.
+    pub fn align_to_byte(&mut self) {
+        if self.bit_offset != 0 {
+            self.bit_offset = 0;
+            self.inner.advance(1);
+        }
+    }
+
+    /// Reads a single bit.
+    pub fn read_bit(&mut self) -> Option<bool> {
+        let byte = self.inner.upcoming_byte()?;
+        let bit = O::bit(byte, self.bit_offset);
+
+        self.bit_offset = self.bit_offset.saturating_add(1);
+        if self.bit_offset == 8 {
+            self.align_to_byte();
+        }
+
+        Some(bit)
+    }
+
+    /// Reads `n` bits (at most 64) into an integer. How the bits make up the integer is
+    /// determined by the bit order of the reader.
+    ///
+    /// Returns `None` without reading anything if there are less than `n` bits left,
+    /// or `n` is more than 64.
+    ///
+    /// ```rust
+    /// use parse_helper::{Lsb0, Msb0, ParseHelper};
+    ///
+    /// let mut bits = ParseHelper::new_byte_oriented(&[0b1100_0101]).into_bit_reader::<Lsb0>();
+    /// assert_eq!(bits.read_bits(3), Some(0b101));
+    /// assert_eq!(bits.read_bits(6), None);
+    /// assert_eq!(bits.read_bits(5), Some(0b11000));
+    ///
+    /// let mut bits = ParseHelper::new_byte_oriented(&[0b1100_0101]).into_bit_reader::<Msb0>();
+    /// assert_eq!(bits.read_bits(3), Some(0b110));
+    /// assert_eq!(bits.read_bits(5), Some(0b00101));
+    /// ```
+    pub fn read_bits(&mut self, n: u32) -> Option<u64> {
+        if n > u64::BITS || n as usize > self.bits_left() {
+            return None;
+        }
+
+        let mut res = 0;
+        for i in 0..n {
+            let bit = self.read_bit()?;
+            res = O::push(res, i, bit);
+        }
+
+        Some(res)
+    }
+
+    /// Reads an unsigned [exponential-Golomb](https://en.wikipedia.org/wiki/Exponential-Golomb_coding)
+    /// coded integer, the `ue(v)` of the H.264 spec.
+    ///
+    /// Returns `None` without reading anything if the input ends before the end of the code,
+    /// or if the value doesn't fit in a `u64`.
+    ///
+    /// ```rust
+    /// use parse_helper::{Msb0, ParseHelper};
+    ///
+    /// // 1, 010, 011, 00100
+    /// let mut bits = ParseHelper::new_byte_oriented(&[0b1010_0110, 0b0100_0000])
+    ///     .into_bit_reader::<Msb0>();
+    /// assert_eq!(bits.read_exp_golomb(), Some(0));
+    /// assert_eq!(bits.read_exp_golomb(), Some(1));
+    /// assert_eq!(bits.read_exp_golomb(), Some(2));
+    /// assert_eq!(bits.read_exp_golomb(), Some(3));
+    /// ```
+    pub fn read_exp_golomb(&mut self) -> Option<u64> {
+        let old = self.create_backup();
+
+        let res = (|| {
+            // codes with 64 or more leading zeros don't fit in a u64, so stop counting there
+            for leading_zeros in 0..u64::BITS {
+                if self.read_bit()? {
+                    let rest = self.read_bits(leading_zeros)?;
+                    return Some(((1 << leading_zeros) | rest).saturating_sub(1));
+                }
+            }
+            None
+        })();
+
+        if res.is_none() {
+            self.restore_backup(old);
+        }
+        res
+    }
+
+    /// Reads a signed [exponential-Golomb](https://en.wikipedia.org/wiki/Exponential-Golomb_coding)
+    /// coded integer, the `se(v)` of the H.264 spec.
+    ///
+    /// Returns `None` without reading anything if the input ends before the end of the code.
+    ///
+    /// ```rust
+    /// use parse_helper::{Msb0, ParseHelper};
+    ///
+    /// // 1, 010, 011, 00100
+    /// let mut bits = ParseHelper::new_byte_oriented(&[0b1010_0110, 0b0100_0000])
+    ///     .into_bit_reader::<Msb0>();
+    /// assert_eq!(bits.read_signed_exp_golomb(), Some(0));
+    /// assert_eq!(bits.read_signed_exp_golomb(), Some(1));
+    /// assert_eq!(bits.read_signed_exp_golomb(), Some(-1));
+    /// assert_eq!(bits.read_signed_exp_golomb(), Some(2));
+    /// ```
+    pub fn read_signed_exp_golomb(&mut self) -> Option<i64> {
+        let k = self.read_exp_golomb()?;
+
+        // `k` is at most `u64::MAX - 1`, so this can't overflow
+        let magnitude = (k / 2) as i64;
+        Some(if k % 2 == 1 {
+            magnitude.saturating_add(1)
+        } else {
+            magnitude.saturating_neg()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Lsb0, Msb0, ParseHelper};
+
+    #[test]
+    fn no_panics_at_end_of_input() {
+        for input in ["", "a", "\u{e9}", "a\u{1f980}"] {
+            let mut ph = ParseHelper::new_byte_oriented(input);
+            ph.skip_bytes(input.len());
+
+            let mut bits = ph.into_bit_reader::<Msb0>();
+            assert_eq!(bits.bits_left(), 0);
+            assert_eq!(bits.read_bit(), None);
+            assert_eq!(bits.read_bits(0), Some(0));
+            assert_eq!(bits.read_bits(65), None);
+            assert_eq!(bits.read_exp_golomb(), None);
+            assert_eq!(bits.read_signed_exp_golomb(), None);
+            bits.align_to_byte();
+            assert!(bits.into_byte_oriented().done());
+        }
+    }
+
+    #[test]
+    fn bit_marks() {
+        let mut bits = ParseHelper::new_byte_oriented(b"\xf0\x0f").into_bit_reader::<Msb0>();
+        assert_eq!(bits.bits_left(), 16);
+
+        assert_eq!(bits.read_bits(4), Some(0xf));
+        let mark = bits.bit_mark();
+        assert_eq!(mark.mark().byte_position(), 0);
+        assert_eq!(mark.bit_offset(), 4);
+
+        assert_eq!(bits.read_bits(8), Some(0x00));
+        let mark = bits.bit_mark();
+        assert_eq!(mark.mark().byte_position(), 1);
+        assert_eq!(mark.bit_offset(), 4);
+        assert_eq!(mark.bit_position(), 12);
+        assert_eq!(bits.bits_left(), 4);
+
+        let ph = bits.into_byte_oriented();
+        assert!(ph.done());
+    }
+
+    #[test]
+    fn read_across_bytes() {
+        let mut bits = ParseHelper::new_byte_oriented(b"\xab\xcd\xef").into_bit_reader::<Msb0>();
+        assert_eq!(bits.read_bits(4), Some(0xa));
+        assert_eq!(bits.read_bits(12), Some(0xbcd));
+        assert!(bits.is_byte_aligned());
+        assert_eq!(bits.read_bits(9), None);
+        assert_eq!(bits.read_bits(8), Some(0xef));
+        assert_eq!(bits.read_bit(), None);
+
+        // a DEFLATE block header: BFINAL = 1, BTYPE = 01
+        let mut bits = ParseHelper::new_byte_oriented(b"\x03").into_bit_reader::<Lsb0>();
+        assert_eq!(bits.read_bit(), Some(true));
+        assert_eq!(bits.read_bits(2), Some(0b01));
+        bits.align_to_byte();
+        assert_eq!(bits.bits_left(), 0);
+    }
+
+    #[test]
+    fn exp_golomb_failure_resets() {
+        // eight leading zeroes but no more bits after it
+        let mut bits = ParseHelper::new_byte_oriented(b"\x00\x80").into_bit_reader::<Msb0>();
+        assert_eq!(bits.read_exp_golomb(), None);
+        assert_eq!(bits.bits_left(), 16);
+
+        // 64 leading zeroes can't fit in a u64
+        let mut bits = ParseHelper::new_byte_oriented(&[0; 17]).into_bit_reader::<Msb0>();
+        assert_eq!(bits.read_exp_golomb(), None);
+        assert_eq!(bits.bits_left(), 17 * 8);
+    }
+
+    #[test]
+    fn exp_golomb_limits() {
+        // 63 leading zeroes, then the one and 63 more ones
+        let max = [
+            0, 0, 0, 0, 0, 0, 0, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+        ];
+        let mut bits = ParseHelper::new_byte_oriented(&max).into_bit_reader::<Msb0>();
+        assert_eq!(bits.read_exp_golomb(), Some(u64::MAX - 1));
+        assert_eq!(bits.bits_left(), 1);
+
+        let mut bits = ParseHelper::new_byte_oriented(&max).into_bit_reader::<Msb0>();
+        assert_eq!(bits.read_signed_exp_golomb(), Some(i64::MIN + 1));
+    }
+}
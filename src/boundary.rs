@@ -1,19 +1,32 @@
-#[allow(unused)]
-mod private {
+pub(crate) mod private {
     use core::{fmt::Debug, hash::Hash};
 
     pub trait BoundaryAssumption:
         Clone + Copy + PartialEq + Eq + PartialOrd + Ord + Hash + Debug
     {
+        // whether a parse helper with this boundary assumption may be at `position` in `input`
+        fn is_boundary(input: &[u8], position: usize) -> bool;
     }
 }
 
 /// Makes no assumption about the offset of the parse helper
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct Byte;
-impl private::BoundaryAssumption for Byte {}
+impl private::BoundaryAssumption for Byte {
+    fn is_boundary(input: &[u8], position: usize) -> bool {
+        position <= input.len()
+    }
+}
 
 /// Assumes the offset of the parse helper is always at utf8 boundaries
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct Char;
-impl private::BoundaryAssumption for Char {}
+impl private::BoundaryAssumption for Char {
+    fn is_boundary(input: &[u8], position: usize) -> bool {
+        match input.get(position) {
+            // utf8 continuation bytes look like 0b10xx_xxxx
+            Some(&b) => b & 0b1100_0000 != 0b1000_0000,
+            None => position == input.len(),
+        }
+    }
+}
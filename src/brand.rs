@@ -0,0 +1,260 @@
+use core::{
+    marker::PhantomData,
+    ops::{Deref, DerefMut, Index},
+    ptr,
+};
+
+use crate::{any::private::SliceRange, Mark, ParseHelper};
+
+// invariant in 'id, so two brands can never be unified into one
+type Brand<'id> = PhantomData<fn(&'id ()) -> &'id ()>;
+
+mod private {
+    use core::ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
+
+    use super::BrandedMark;
+    use crate::{Byte, Mark};
+
+    pub trait Unbrand<'id, B> {
+        type Unbranded;
+
+        fn unbrand(self) -> Self::Unbranded;
+    }
+
+    // NOTE: just like `SliceRange`, inclusive ranges are only allowed on `Byte` oriented marks.
+
+    impl<'id, B> Unbrand<'id, B> for Range<BrandedMark<'id, B>> {
+        type Unbranded = Range<Mark<B>>;
+
+        fn unbrand(self) -> Self::Unbranded {
+            self.start.mark..self.end.mark
+        }
+    }
+    impl<'id, B> Unbrand<'id, B> for RangeFrom<BrandedMark<'id, B>> {
+        type Unbranded = RangeFrom<Mark<B>>;
+
+        fn unbrand(self) -> Self::Unbranded {
+            self.start.mark..
+        }
+    }
+    impl<'id, B> Unbrand<'id, B> for RangeTo<BrandedMark<'id, B>> {
+        type Unbranded = RangeTo<Mark<B>>;
+
+        fn unbrand(self) -> Self::Unbranded {
+            ..self.end.mark
+        }
+    }
+    impl<'id, B> Unbrand<'id, B> for RangeFull {
+        type Unbranded = RangeFull;
+
+        fn unbrand(self) -> Self::Unbranded {
+            ..
+        }
+    }
+    impl<'id> Unbrand<'id, Byte> for RangeToInclusive<BrandedMark<'id, Byte>> {
+        type Unbranded = RangeToInclusive<Mark<Byte>>;
+
+        fn unbrand(self) -> Self::Unbranded {
+            ..=self.end.mark
+        }
+    }
+    impl<'id> Unbrand<'id, Byte> for RangeInclusive<BrandedMark<'id, Byte>> {
+        type Unbranded = RangeInclusive<Mark<Byte>>;
+
+        fn unbrand(self) -> Self::Unbranded {
+            let (start, end) = self.into_inner();
+            start.mark..=end.mark
+        }
+    }
+}
+
+/// A [`Mark`] that can only be used to slice the input it was created from.
+///
+/// Created by [`Branded::mark`]. The `'id` lifetime is unique to a single call to
+/// [`ParseHelper::branded`], which makes it impossible to use the mark with any other input.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct BrandedMark<'id, B> {
+    mark: Mark<B>,
+    brand: Brand<'id>,
+}
+
+impl<'id, B> BrandedMark<'id, B> {
+    /// Forget which input this mark belongs to, to use it with the regular [`Mark`] api.
+    pub fn unbrand(self) -> Mark<B> {
+        self.mark
+    }
+
+    /// get the position in the input of this mark.
+    pub fn byte_position(&self) -> usize {
+        self.mark.byte_position()
+    }
+}
+
+/// A parse helper that hands out [`BrandedMark`]s, created by [`ParseHelper::branded`].
+///
+/// Derefs to the [`ParseHelper`] it was created from, so all the usual methods are available.
+pub struct Branded<'p, 'a, 'id, T: ?Sized, B> {
+    inner: &'p mut ParseHelper<'a, T, B>,
+    // the input at the moment the brand was created, to check that it isn't swapped out
+    // for a different one while marks are handed out.
+    input: &'a T,
+    brand: Brand<'id>,
+}
+
+impl<'p, 'a, 'id, T: ?Sized, B> Deref for Branded<'p, 'a, 'id, T, B> {
+    type Target = ParseHelper<'a, T, B>;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner
+    }
+}
+
+impl<'p, 'a, 'id, T: ?Sized, B> DerefMut for Branded<'p, 'a, 'id, T, B> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.inner
+    }
+}
+
+impl<'a, T: ?Sized, B> ParseHelper<'a, T, B> {
+    /// Opt in to branded marks. Within the closure, [`Branded::mark`] creates marks
+    /// that [`Branded::slice`] only accepts when they come from this same input.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("ab cd");
+    ///
+    /// let word = ph.branded(|mut ph| {
+    ///     let start = ph.mark();
+    ///     ph.accept_until_whitespace();
+    ///     let end = ph.mark();
+    ///
+    ///     ph.slice(start..end)
+    /// });
+    /// assert_eq!(word, "ab");
+    /// ```
+    ///
+    /// Marks of different inputs can't be mixed:
+    ///
+    /// ```compile_fail
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut a = ParseHelper::new_char_oriented("ab cd");
+    /// let mut b = ParseHelper::new_char_oriented("ef");
+    ///
+    /// a.branded(|a| {
+    ///     b.branded(|b| {
+    ///         let end = b.mark();
+    ///         a.slice(..end);
+    ///     })
+    /// });
+    /// ```
+    ///
+    /// Replacing the input of the parse helper inside the closure (for example by restoring a
+    /// backup of another parse helper through [`DerefMut`]) is a logic error. It is caught by
+    /// [`Branded::mark`] in debug builds, in release builds [`Branded::slice`] may panic.
+    ///
+    /// [`DerefMut`]: core::ops::DerefMut
+    pub fn branded<R>(&mut self, f: impl for<'id> FnOnce(Branded<'_, 'a, 'id, T, B>) -> R) -> R {
+        let input = self.input;
+        f(Branded {
+            inner: self,
+            input,
+            brand: PhantomData,
+        })
+    }
+}
+
+impl<'p, 'a, 'id, T: ?Sized, B> Branded<'p, 'a, 'id, T, B> {
+    /// Creates a branded mark at the current position of the parse helper.
+    pub fn mark(&self) -> BrandedMark<'id, B> {
+        debug_assert!(
+            ptr::eq(self.input, self.inner.input),
+            "input of a branded parse helper was replaced"
+        );
+
+        BrandedMark {
+            mark: self.inner.mark(),
+            brand: PhantomData,
+        }
+    }
+
+    /// Slices the input between two branded marks. Like [`ParseHelper::slice`], but the marks
+    /// are known to come from this parse helper. As long as its input isn't replaced (see
+    /// [`ParseHelper::branded`]), that means they are in bounds and, for
+    /// [`Char`](crate::Char) oriented parse helpers, on utf8 boundaries.
+    ///
+    /// # Panics
+    ///
+    /// When the start of the range lies after the end, or when the input was replaced and a mark
+    /// made after that lies outside of the original input, or isn't on one of its utf8 boundaries.
+    /// In debug builds [`mark`](Self::mark) already panics when the input was replaced.
+    pub fn slice<R>(
+        &self,
+        range: R,
+    ) -> &'a <T as Index<<R::Unbranded as SliceRange<'a, B, T>>::RangeTy>>::Output
+    where
+        R: private::Unbrand<'id, B>,
+        R::Unbranded: SliceRange<'a, B, T>,
+        T: Index<<R::Unbranded as SliceRange<'a, B, T>>::RangeTy>,
+    {
+        range.unbrand().slice(self.input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ParseHelper;
+
+    #[test]
+    fn branded_marks() {
+        let mut ph = ParseHelper::new_byte_oriented("hello world");
+
+        let (hello, world) = ph.branded(|mut ph| {
+            let start = ph.mark();
+            ph.accept_until_byte(b' ');
+            let space = ph.mark();
+            ph.skip_byte();
+            let after_space = ph.mark();
+            ph.accept_until_byte(b'!');
+
+            assert_eq!(ph.slice(..), "hello world");
+            assert_eq!(ph.slice(start..=space), "hello ");
+            assert_eq!(ph.slice(..=space), "hello ");
+            assert_eq!(space.unbrand().byte_position(), 5);
+
+            (ph.slice(start..space), ph.slice(after_space..))
+        });
+
+        assert_eq!(hello, "hello");
+        assert_eq!(world, "world");
+        assert!(ph.done());
+    }
+
+    #[test]
+    fn branded_marks_survive_backups() {
+        let mut ph = ParseHelper::new_char_oriented("abc");
+
+        ph.branded(|mut ph| {
+            let backup = ph.create_backup();
+            ph.accept_char('a');
+            let mark = ph.mark();
+            ph.restore_backup(backup);
+
+            assert_eq!(ph.slice(..mark), "a");
+            assert_eq!(ph.slice(mark..), "bc");
+        });
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic = "input of a branded parse helper was replaced"]
+    fn branded_input_replaced() {
+        let mut ph = ParseHelper::new_char_oriented("abc");
+
+        ph.branded(|mut ph| {
+            ph.restore_backup(ParseHelper::new_char_oriented("def"));
+            ph.mark();
+        });
+    }
+}
@@ -1,20 +1,49 @@
 use core::mem;
 
-use crate::{Byte, Char, ParseHelper};
+use crate::{
+    trace::{ByteLiteral, ByteStrLiteral, Closure},
+    Byte, Char, OutOfBounds, ParseHelper,
+};
 
 impl<'a, T: ?Sized> ParseHelper<'a, T, Byte>
 where
     T: AsRef<[u8]>,
 {
     /// discard the upcoming byte
+    ///
+    /// # Panics
+    ///
+    /// When the end of the input was already reached.
+    /// See [`try_skip_byte`](Self::try_skip_byte) for a version that doesn't panic.
     pub fn skip_byte(&mut self) {
         self.skip_bytes(1);
     }
 
     /// discard the upcoming `n` byte
+    ///
+    /// # Panics
+    ///
+    /// When there are less than `n` bytes left.
+    /// See [`try_skip_bytes`](Self::try_skip_bytes) for a version that doesn't panic.
     pub fn skip_bytes(&mut self, n: usize) {
         assert!(n <= self.bytes_left(), "end of input reached");
-        self.byte_position += n;
+        self.advance(n);
+    }
+
+    /// discard the upcoming byte, or return an error if the end of the input was already reached.
+    pub fn try_skip_byte(&mut self) -> Result<(), OutOfBounds> {
+        self.try_skip_bytes(1)
+    }
+
+    /// discard the upcoming `n` bytes, or return an error without skipping anything if there
+    /// are less than `n` bytes left.
+    pub fn try_skip_bytes(&mut self, n: usize) -> Result<(), OutOfBounds> {
+        if n > self.bytes_left() {
+            return Err(OutOfBounds);
+        }
+
+        self.advance(n);
+        Ok(())
     }
 }
 
@@ -31,9 +60,7 @@ where
 
     /// skips until a utf8 boundary is reached (which is never more than 4 bytes)
     pub fn skip_to_next_utf8_char_boundary(&mut self) {
-        while !self.is_at_utf8_boundary() {
-            self.skip_byte();
-        }
+        while !self.is_at_utf8_boundary() && self.try_skip_byte().is_ok() {}
     }
 
     /// Turn this byte oriented parse helper into a char-oriented parser,
@@ -68,12 +95,17 @@ where
 {
     /// accepts a single byte from the input
     pub fn leftover(&self) -> &'a [u8] {
-        &self.input.as_ref()[self.byte_position..]
+        self.input
+            .as_ref()
+            .get(self.byte_position..)
+            .unwrap_or_default()
     }
 
     /// accepts a single byte from the input
     pub fn accept_byte(&mut self, c: u8) -> bool {
-        self.accept_byte_with(|x| c == x).is_some()
+        self.traced("accept_byte", &ByteLiteral(c), |ph| {
+            ph.accept_byte_with(|x| c == x).is_some()
+        })
     }
 
     /// accepts a sequence of bytes-like values from the input
@@ -95,63 +127,66 @@ where
     /// ```
     pub fn accept(&mut self, bytes: impl AsRef<[u8]>) -> Option<&'a [u8]> {
         let bytes = bytes.as_ref();
-        if bytes.len() > self.bytes_left() {
-            return None;
-        }
-
-        let equivalent_input =
-            &self.input.as_ref()[self.byte_position..self.byte_position + bytes.len()];
-
-        if bytes == equivalent_input {
-            self.byte_position += bytes.len();
-            Some(equivalent_input)
-        } else {
-            None
-        }
+        self.traced("accept", &ByteStrLiteral(bytes), |ph| {
+            let equivalent_input = ph.leftover().get(..bytes.len())?;
+
+            if bytes == equivalent_input {
+                ph.advance(bytes.len());
+                Some(equivalent_input)
+            } else {
+                None
+            }
+        })
     }
 
     /// Accepts until the closure matches the current byte.
     ///
     /// Returns what's accepted until then, but not including the matching character.
     pub fn accept_until_byte_with(&mut self, f: impl Fn(u8) -> bool) -> &'a [u8] {
-        let start = self.byte_position;
+        self.traced("accept_until_byte_with", &Closure, |ph| {
+            let start = ph.byte_position;
 
-        // while it doesn't match...
-        while let Some(next_byte) = self.upcoming_byte() {
-            if f(next_byte) {
-                break;
-            }
+            // while it doesn't match...
+            while let Some(next_byte) = ph.upcoming_byte() {
+                if f(next_byte) {
+                    break;
+                }
 
-            self.byte_position += 1;
-        }
+                ph.advance(1);
+            }
 
-        let end = self.byte_position;
+            let end = ph.byte_position;
 
-        // * `begin` must not exceed `end`. (we accepted 0 or more characters)
-        // * `begin` and `end` must be byte positions within the slice. (byte_position
-        //   starts at 0 and never goes out of bounds, `upcoming_char` tests this)
-        unsafe { self.input.as_ref().get_unchecked(start..end) }
+            // * `begin` must not exceed `end`. (we accepted 0 or more characters)
+            // * `begin` and `end` must be byte positions within the slice. (byte_position
+            //   starts at 0 and never goes out of bounds, `upcoming_char` tests this)
+            unsafe { ph.input.as_ref().get_unchecked(start..end) }
+        })
     }
 
     /// Accepts until a specific character is encountered
     ///
     /// Returns what's accepted until then, but not including the matching character.
     pub fn accept_until_byte(&mut self, c: u8) -> &'a [u8] {
-        self.accept_until_byte_with(|x| x == c)
+        self.traced("accept_until_byte", &ByteLiteral(c), |ph| {
+            ph.accept_until_byte_with(|x| x == c)
+        })
     }
 
     /// Accepts a byte if the passed closure evaluates to true.
     ///
     /// Returns what it accepted, if anything
     pub fn accept_byte_with(&mut self, f: impl Fn(u8) -> bool) -> Option<u8> {
-        let b = self.upcoming_byte()?;
-        if f(b) {
-            self.skip_byte();
-
-            Some(b)
-        } else {
-            None
-        }
+        self.traced("accept_byte_with", &Closure, |ph| {
+            let b = ph.upcoming_byte()?;
+            if f(b) {
+                ph.advance(1);
+
+                Some(b)
+            } else {
+                None
+            }
+        })
     }
 }
 
@@ -0,0 +1,236 @@
+use core::fmt::{self, Debug, Formatter};
+
+use crate::{Byte, ParseHelper};
+
+/// A set of bytes, used as a table-driven predicate instead of a closure.
+///
+/// Unlike closures, byte sets can be built and used in `const` contexts,
+/// see [`ParseHelper::const_accept_byte_in`].
+///
+/// ```
+/// use parse_helper::ByteSet;
+///
+/// const IDENT: ByteSet = ByteSet::ASCII_ALPHANUMERIC.with(b'_');
+///
+/// assert!(IDENT.contains(b'_'));
+/// assert!(IDENT.contains(b'z'));
+/// assert!(!IDENT.contains(b'-'));
+/// assert_eq!(IDENT.len(), 63);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ByteSet {
+    // bytes 0 to 127
+    low: u128,
+    // bytes 128 to 255
+    high: u128,
+}
+
+impl ByteSet {
+    /// The set without any bytes.
+    pub const EMPTY: Self = Self { low: 0, high: 0 };
+    /// The set of all bytes.
+    pub const ALL: Self = Self::EMPTY.complement();
+    /// `0` to `9`
+    pub const ASCII_DIGIT: Self = Self::range(b'0', b'9');
+    /// `0` to `9`, `a` to `f` and `A` to `F`
+    pub const ASCII_HEXDIGIT: Self = Self::ASCII_DIGIT
+        .union(Self::range(b'a', b'f'))
+        .union(Self::range(b'A', b'F'));
+    /// `a` to `z` and `A` to `Z`
+    pub const ASCII_ALPHABETIC: Self = Self::range(b'a', b'z').union(Self::range(b'A', b'Z'));
+    /// [`ASCII_ALPHABETIC`](Self::ASCII_ALPHABETIC) and [`ASCII_DIGIT`](Self::ASCII_DIGIT)
+    pub const ASCII_ALPHANUMERIC: Self = Self::ASCII_ALPHABETIC.union(Self::ASCII_DIGIT);
+    /// The same whitespace as [`u8::is_ascii_whitespace`]
+    pub const ASCII_WHITESPACE: Self = Self::from_bytes(b" \t\n\x0C\r");
+
+    /// Creates an empty set.
+    pub const fn new() -> Self {
+        Self::EMPTY
+    }
+
+    /// Creates a set of the given bytes.
+    pub const fn from_bytes(bytes: &[u8]) -> Self {
+        let mut res = Self::EMPTY;
+        let mut rest = bytes;
+        while let [b, tail @ ..] = rest {
+            res = res.with(*b);
+            rest = tail;
+        }
+        res
+    }
+
+    /// Creates a set of all bytes from `start` up to and including `end`.
+    ///
+    /// The set is empty when `start` lies after `end`.
+    pub const fn range(start: u8, end: u8) -> Self {
+        let mut res = Self::EMPTY;
+        let mut b = start;
+        while b <= end {
+            res = res.with(b);
+            if b == u8::MAX {
+                break;
+            }
+            b = b.saturating_add(1);
+        }
+        res
+    }
+
+    /// Returns this set with `b` added.
+    pub const fn with(self, b: u8) -> Self {
+        if b < 128 {
+            Self {
+                low: self.low | 1 << b,
+                ..self
+            }
+        } else {
+            Self {
+                high: self.high | 1 << (b & 0x7f),
+                ..self
+            }
+        }
+    }
+
+    /// Returns this set without `b`.
+    pub const fn without(self, b: u8) -> Self {
+        self.intersection(Self::EMPTY.with(b).complement())
+    }
+
+    /// Returns the bytes in either set.
+    pub const fn union(self, other: Self) -> Self {
+        Self {
+            low: self.low | other.low,
+            high: self.high | other.high,
+        }
+    }
+
+    /// Returns the bytes in both sets.
+    pub const fn intersection(self, other: Self) -> Self {
+        Self {
+            low: self.low & other.low,
+            high: self.high & other.high,
+        }
+    }
+
+    /// Returns the bytes that are not in this set.
+    pub const fn complement(self) -> Self {
+        Self {
+            low: !self.low,
+            high: !self.high,
+        }
+    }
+
+    /// Returns `true` if `b` is in the set.
+    pub const fn contains(&self, b: u8) -> bool {
+        if b < 128 {
+            self.low >> b & 1 == 1
+        } else {
+            self.high >> (b & 0x7f) & 1 == 1
+        }
+    }
+
+    /// The number of bytes in the set.
+    pub const fn len(&self) -> usize {
+        self.low.count_ones().saturating_add(self.high.count_ones()) as usize
+    }
+
+    /// Returns `true` if there are no bytes in the set.
+    pub const fn is_empty(&self) -> bool {
+        self.low == 0 && self.high == 0
+    }
+
+    /// Iterates over the bytes in the set, in order.
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..=u8::MAX).filter(|&b| self.contains(b))
+    }
+}
+
+impl Debug for ByteSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter().map(char::from)).finish()
+    }
+}
+
+impl FromIterator<u8> for ByteSet {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::EMPTY, Self::with)
+    }
+}
+
+impl<'a, T: ?Sized> ParseHelper<'a, T, Byte>
+where
+    T: AsRef<[u8]>,
+{
+    /// Accepts a byte if it is in the set.
+    ///
+    /// Returns what it accepted, if anything
+    ///
+    /// ```rust
+    /// use parse_helper::{ByteSet, ParseHelper};
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented("x1");
+    ///
+    /// assert_eq!(ph.accept_byte_in(&ByteSet::ASCII_DIGIT), None);
+    /// assert_eq!(ph.accept_byte_in(&ByteSet::ASCII_ALPHABETIC), Some(b'x'));
+    /// assert_eq!(ph.accept_byte_in(&ByteSet::ASCII_DIGIT), Some(b'1'));
+    /// ```
+    pub fn accept_byte_in(&mut self, set: &ByteSet) -> Option<u8> {
+        self.traced("accept_byte_in", set, |ph| {
+            ph.accept_byte_with(|b| set.contains(b))
+        })
+    }
+
+    /// Accepts until the current byte is in the set.
+    ///
+    /// Returns what's accepted until then, but not including the byte in the set.
+    ///
+    /// ```rust
+    /// use parse_helper::{ByteSet, ParseHelper};
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented("123abc");
+    ///
+    /// let digits = ph.accept_until_byte_in(&ByteSet::ASCII_DIGIT.complement());
+    /// assert_eq!(digits, b"123");
+    /// assert_eq!(ph.leftover(), b"abc");
+    /// ```
+    pub fn accept_until_byte_in(&mut self, set: &ByteSet) -> &'a [u8] {
+        self.traced("accept_until_byte_in", set, |ph| {
+            ph.accept_until_byte_with(|b| set.contains(b))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ByteSet;
+
+    #[test]
+    fn matches_std_predicates() {
+        for b in 0..=u8::MAX {
+            assert_eq!(ByteSet::ASCII_DIGIT.contains(b), b.is_ascii_digit());
+            assert_eq!(ByteSet::ASCII_HEXDIGIT.contains(b), b.is_ascii_hexdigit());
+            assert_eq!(
+                ByteSet::ASCII_ALPHABETIC.contains(b),
+                b.is_ascii_alphabetic()
+            );
+            assert_eq!(
+                ByteSet::ASCII_ALPHANUMERIC.contains(b),
+                b.is_ascii_alphanumeric()
+            );
+            assert_eq!(
+                ByteSet::ASCII_WHITESPACE.contains(b),
+                b.is_ascii_whitespace()
+            );
+            assert!(ByteSet::ALL.contains(b));
+            assert!(!ByteSet::EMPTY.contains(b));
+        }
+
+        assert_eq!(ByteSet::range(250, 255).len(), 6);
+        assert!(ByteSet::range(2, 1).is_empty());
+        assert_eq!(ByteSet::ALL.without(200).len(), 255);
+        assert_eq!(
+            [0, 200, 0].into_iter().collect::<ByteSet>(),
+            ByteSet::from_bytes(&[200, 0])
+        );
+        assert_eq!(format!("{:?}", ByteSet::from_bytes(b"a0")), "{'0', 'a'}");
+    }
+}
@@ -0,0 +1,408 @@
+use alloc::{
+    borrow::{Cow, ToOwned},
+    string::String,
+    vec::Vec,
+};
+use core::{
+    fmt::{self, Display, Formatter},
+    ops::Range,
+};
+
+use crate::{Char, Mark, ParseHelper};
+
+/// Text that is stored in multiple chunks instead of one contiguous string,
+/// like the rope of a text editor.
+///
+/// Parse it with [`ParseHelper::new_chunked`]. Implemented for slices of string slices,
+/// and for [`Rope`].
+pub trait ChunkedInput {
+    /// The length of all chunks together, in bytes.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if there is no text in any of the chunks.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the chunk that contains `byte_position`, together with the position it starts at.
+    ///
+    /// Returns `None` when `byte_position` is at or past the end of the text.
+    /// Empty chunks never contain any position.
+    fn chunk_at(&self, byte_position: usize) -> Option<(usize, &str)>;
+}
+
+impl<S: AsRef<str>> ChunkedInput for [S] {
+    fn len(&self) -> usize {
+        self.iter()
+            .fold(0, |len, chunk| len.saturating_add(chunk.as_ref().len()))
+    }
+
+    fn chunk_at(&self, byte_position: usize) -> Option<(usize, &str)> {
+        let mut start: usize = 0;
+        for chunk in self {
+            let chunk = chunk.as_ref();
+            let end = start.checked_add(chunk.len())?;
+            if byte_position < end {
+                return Some((start, chunk));
+            }
+            start = end;
+        }
+
+        None
+    }
+}
+
+/// A simple rope: text stored as a list of chunks, that can be grown without copying what
+/// is already there.
+///
+/// ```
+/// use parse_helper::{ChunkedInput, Rope};
+///
+/// let mut rope = Rope::new();
+/// rope.push("hello ");
+/// rope.push(String::from("world"));
+///
+/// assert_eq!(rope.len(), 11);
+/// assert_eq!(rope.chunk_at(7), Some((6, "world")));
+/// assert_eq!(rope.to_string(), "hello world");
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Rope {
+    chunks: Vec<String>,
+    // the position every chunk starts at
+    starts: Vec<usize>,
+    len: usize,
+}
+
+impl Rope {
+    /// Creates an empty rope.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a chunk of text to the end of the rope.
+    pub fn push(&mut self, chunk: impl Into<String>) {
+        let chunk = chunk.into();
+        if chunk.is_empty() {
+            return;
+        }
+
+        self.starts.push(self.len);
+        self.len = self.len.saturating_add(chunk.len());
+        self.chunks.push(chunk);
+    }
+
+    /// Iterates over the chunks of the rope, in order.
+    pub fn chunks(&self) -> impl Iterator<Item = &str> {
+        self.chunks.iter().map(String::as_str)
+    }
+}
+
+impl ChunkedInput for Rope {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn chunk_at(&self, byte_position: usize) -> Option<(usize, &str)> {
+        if byte_position >= self.len {
+            return None;
+        }
+
+        // the last chunk starting at or before the position
+        let idx = self
+            .starts
+            .partition_point(|&start| start <= byte_position)
+            .checked_sub(1)?;
+
+        Some((*self.starts.get(idx)?, self.chunks.get(idx)?))
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for Rope {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let mut rope = Self::new();
+        for chunk in iter {
+            rope.push(chunk);
+        }
+        rope
+    }
+}
+
+impl Display for Rope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.chunks().try_for_each(|chunk| f.write_str(chunk))
+    }
+}
+
+/// Wraps [`ChunkedInput`] to be parsed by a [`ParseHelper`], created by
+/// [`ParseHelper::new_chunked`].
+#[repr(transparent)]
+pub struct Chunked<C: ?Sized>(C);
+
+impl<C: ?Sized + ChunkedInput> Chunked<C> {
+    fn new(input: &C) -> &Self {
+        // Safety: `Chunked` is a transparent wrapper around `C`
+        unsafe { &*(input as *const C as *const Self) }
+    }
+
+    /// Returns the wrapped input.
+    pub fn inner(&self) -> &C {
+        &self.0
+    }
+}
+
+impl<'a, C: ?Sized + ChunkedInput> ParseHelper<'a, Chunked<C>, Char> {
+    /// Creates a new [`ParseHelper`] for text stored in chunks, that assumes
+    /// steps can only be taken one utf8 codepoint at a time.
+    ///
+    /// Slices of the input are returned as a [`Cow`], which only owns the text
+    /// when it crosses the edge of a chunk.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    /// use std::borrow::Cow;
+    ///
+    /// let chunks = ["let x", " = 1", "0;"];
+    /// let mut ph = ParseHelper::new_chunked(chunks.as_slice());
+    ///
+    /// assert_eq!(ph.accept("let"), Some(Cow::Borrowed("let")));
+    /// ph.accept_whitespace();
+    /// assert_eq!(ph.accept_until_whitespace(), Cow::Borrowed("x"));
+    /// assert_eq!(ph.accept(" = "), Some(Cow::Owned(" = ".to_string())));
+    /// assert_eq!(ph.accept_until_char(';'), Cow::<str>::Owned("10".to_string()));
+    /// assert_eq!(ph.accept_char(';'), Some(";"));
+    /// assert!(ph.done());
+    /// ```
+    pub fn new_chunked(input: &'a C) -> Self {
+        Self::with_boundary_assumption(Chunked::new(input))
+    }
+
+    // the rest of the chunk at a position
+    fn rest_of_chunk(&self, byte_position: usize) -> Option<&'a str> {
+        let input: &'a C = &self.input.0;
+        let (start, chunk) = input.chunk_at(byte_position)?;
+        chunk.get(byte_position.checked_sub(start)?..)
+    }
+
+    /// Returns `true` if the end of the input has been reached.
+    pub fn done(&self) -> bool {
+        self.byte_position >= self.input.0.len()
+    }
+
+    /// Returns how many bytes are left to parse
+    pub fn bytes_left(&self) -> usize {
+        self.input.0.len().saturating_sub(self.byte_position)
+    }
+
+    /// returns the next character to be accepted
+    pub fn upcoming_char(&self) -> Option<char> {
+        self.rest_of_chunk(self.byte_position)?.chars().next()
+    }
+
+    /// Accepts a character if the passed closure evaluates to true.
+    ///
+    /// Returns what it accepted, if anything. A character is never split over chunks,
+    /// so this is always borrowed.
+    pub fn accept_char_with(&mut self, f: impl Fn(char) -> bool) -> Option<&'a str> {
+        let rest = self.rest_of_chunk(self.byte_position)?;
+        let c = rest.chars().next().filter(|&c| f(c))?;
+
+        self.advance(c.len_utf8());
+        rest.get(..c.len_utf8())
+    }
+
+    /// accepts a single char from the input.
+    pub fn accept_char(&mut self, c: char) -> Option<&'a str> {
+        self.accept_char_with(|x| x == c)
+    }
+
+    /// Accepts a sequence of string-like values from the input, which may be spread over
+    /// multiple chunks.
+    pub fn accept(&mut self, s: impl AsRef<str>) -> Option<Cow<'a, str>> {
+        let s = s.as_ref();
+        let mut position = self.byte_position;
+        let mut rest = s.as_bytes();
+        let mut res = Cow::Borrowed("");
+
+        while !rest.is_empty() {
+            let chunk = self.rest_of_chunk(position)?;
+            let len = chunk.len().min(rest.len());
+            let (part, next) = rest.split_at(len);
+
+            if chunk.as_bytes().get(..len)? != part {
+                return None;
+            }
+
+            if position == self.byte_position && next.is_empty() {
+                res = Cow::Borrowed(chunk.get(..len)?);
+            } else {
+                res = Cow::Owned(s.to_owned());
+            }
+
+            position = position.saturating_add(len);
+            rest = next;
+        }
+
+        self.byte_position = position;
+        Some(res)
+    }
+
+    /// Accepts until the closure matches the current character, which may be in another chunk.
+    ///
+    /// Returns what's accepted until then, but not including the matching character.
+    pub fn accept_until_char_with(&mut self, f: impl Fn(char) -> bool) -> Cow<'a, str> {
+        let mut res = Cow::Borrowed("");
+
+        while let Some(chunk) = self.rest_of_chunk(self.byte_position) {
+            let len = chunk.find(&f).unwrap_or(chunk.len());
+            let part = chunk.get(..len).unwrap_or_default();
+
+            // only copy when a second chunk adds something, so spans that end at the end of a
+            // chunk stay borrowed
+            if res.is_empty() {
+                res = Cow::Borrowed(part);
+            } else if !part.is_empty() {
+                res.to_mut().push_str(part);
+            }
+
+            self.advance(len);
+            if len < chunk.len() {
+                break;
+            }
+        }
+
+        res
+    }
+
+    /// Accepts until a specific character is encountered
+    ///
+    /// Returns what's accepted until then, but not including the matching character.
+    pub fn accept_until_char(&mut self, c: char) -> Cow<'a, str> {
+        self.accept_until_char_with(|x| x == c)
+    }
+
+    /// Accepts until whitespace is encountered
+    ///
+    /// Returns what's accepted until then, but not including the whitespace
+    pub fn accept_until_whitespace(&mut self) -> Cow<'a, str> {
+        self.accept_until_char_with(|x| x.is_whitespace())
+    }
+
+    /// Accepts a single whitespace character.
+    pub fn accept_whitespace(&mut self) -> Option<&'a str> {
+        self.accept_char_with(|x| x.is_whitespace())
+    }
+
+    /// Accepts a sequence of zero or more whitespace characters.
+    pub fn accept_zero_or_more_whitespace(&mut self) -> Cow<'a, str> {
+        self.accept_until_char_with(|x| !x.is_whitespace())
+    }
+
+    /// Returns the text between two marks, which is only owned when it's spread over
+    /// multiple chunks.
+    ///
+    /// Returns `None` when the range is out of bounds of the input, or the start of the range
+    /// lies after the end.
+    pub fn slice_chunks(&self, range: Range<Mark<Char>>) -> Option<Cow<'a, str>> {
+        let (start, end) = (range.start.byte_position(), range.end.byte_position());
+        if start > end || end > self.input.0.len() {
+            return None;
+        }
+
+        let mut position = start;
+        let mut res = Cow::Borrowed("");
+
+        while position < end {
+            let chunk = self.rest_of_chunk(position)?;
+            let part = chunk.get(..chunk.len().min(end.saturating_sub(position)))?;
+
+            if res.is_empty() {
+                res = Cow::Borrowed(part);
+            } else if !part.is_empty() {
+                res.to_mut().push_str(part);
+            }
+            position = position.saturating_add(part.len());
+        }
+
+        Some(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::Cow;
+
+    use crate::{ChunkedInput, ParseHelper, Rope};
+
+    #[test]
+    fn across_chunks() {
+        let rope: Rope = ["fn ma", "", "in()", " {", "}", "🦀", "é"]
+            .into_iter()
+            .collect();
+        assert_eq!(rope.chunk_at(5), Some((5, "in()")));
+        assert_eq!(rope.chunk_at(rope.len()), None);
+
+        let mut ph = ParseHelper::new_chunked(&rope);
+        let start = ph.mark();
+        assert_eq!(ph.accept("fn "), Some(Cow::Borrowed("fn ")));
+        assert_eq!(ph.accept("mainn"), None);
+        assert!(matches!(ph.accept("main"), Some(Cow::Owned(s)) if s == "main"));
+        assert_eq!(ph.accept_until_char('{'), "() ");
+        assert_eq!(ph.accept_char('{'), Some("{"));
+        assert_eq!(ph.accept_char('}'), Some("}"));
+        assert_eq!(ph.upcoming_char(), Some('🦀'));
+        assert_eq!(ph.accept_until_char('x'), "🦀é");
+        assert!(ph.done());
+        assert_eq!(ph.accept("x"), None);
+        assert_eq!(ph.accept(""), Some(Cow::Borrowed("")));
+
+        let end = ph.mark();
+        assert_eq!(ph.slice_chunks(start..end).unwrap(), rope.to_string());
+        assert_eq!(ph.slice_chunks(end..start), None);
+    }
+
+    #[test]
+    fn borrowed_at_chunk_edges() {
+        let rope: Rope = ["ab", "cd"].into_iter().collect();
+        let mut ph = ParseHelper::new_chunked(&rope);
+        assert!(matches!(ph.accept_until_char('c'), Cow::Borrowed("ab")));
+        assert!(matches!(ph.accept_until_char('x'), Cow::Borrowed("cd")));
+
+        let rope: Rope = ["let x", " = 1", "0;"].into_iter().collect();
+        let mut ph = ParseHelper::new_chunked(&rope);
+        let start = ph.mark();
+        assert!(matches!(ph.accept_until_whitespace(), Cow::Borrowed("let")));
+        ph.accept_whitespace();
+        assert!(matches!(ph.accept_until_whitespace(), Cow::Borrowed("x")));
+        assert!(matches!(
+            ph.slice_chunks(start..ph.mark()),
+            Some(Cow::Borrowed("let x"))
+        ));
+        ph.accept(" = ");
+        assert!(matches!(ph.accept_until_char(';'), Cow::Owned(s) if s == "10"));
+    }
+
+    #[test]
+    fn slice_of_str_slices() {
+        let chunks = [String::from("ab"), String::from("cd")];
+        let mut ph = ParseHelper::new_chunked(chunks.as_slice());
+        assert_eq!(ph.bytes_left(), 4);
+
+        let ((), first) = ph.span_of(|ph| {
+            ph.accept_char('a');
+        });
+        ph.accept_until_char('d');
+        let ((), last) = ph.span_of(|ph| {
+            ph.accept_char('d');
+        });
+
+        assert!(matches!(
+            ph.slice_chunks(first.range()),
+            Some(Cow::Borrowed("a"))
+        ));
+        assert!(matches!(
+            ph.slice_chunks(first.join(last).range()),
+            Some(Cow::Owned(s)) if s == "abcd"
+        ));
+    }
+}
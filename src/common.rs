@@ -1,3 +1,4 @@
+#[cfg(feature = "icu")]
 use core::ops::{Index, Range};
 
 use crate::{Char, ParseHelper};
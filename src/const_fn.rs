@@ -0,0 +1,210 @@
+use crate::{Byte, ByteSet, ParseHelper};
+
+/// `const` counterparts of the byte oriented operations, for parsing at compile time.
+///
+/// Trait methods can't be called in `const fn`, so these only work on byte slices
+/// (use [`str::as_bytes`] for strings) and take a [`ByteSet`] instead of a closure.
+/// They are never [traced](ParseHelper::record_trace).
+///
+/// ```
+/// use parse_helper::{Byte, ByteSet, ParseHelper};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Version {
+///     major: u32,
+///     minor: u32,
+///     patch: u32,
+/// }
+///
+/// const fn number(ph: &mut ParseHelper<[u8], Byte>) -> u32 {
+///     let digits = ph.const_accept_until_byte_in(&ByteSet::ASCII_DIGIT.complement());
+///     assert!(!digits.is_empty(), "expected a number");
+///
+///     let mut res = 0;
+///     let mut rest = digits;
+///     while let [digit, tail @ ..] = rest {
+///         res = res * 10 + (*digit - b'0') as u32;
+///         rest = tail;
+///     }
+///     res
+/// }
+///
+/// const fn parse_version(s: &str) -> Version {
+///     let mut ph = ParseHelper::new_byte_oriented(s.as_bytes());
+///     let major = number(&mut ph);
+///     assert!(ph.const_accept_byte(b'.'), "expected a `.`");
+///     let minor = number(&mut ph);
+///     assert!(ph.const_accept_byte(b'.'), "expected a `.`");
+///     let patch = number(&mut ph);
+///     assert!(ph.const_done(), "expected the end of the version");
+///
+///     Version { major, minor, patch }
+/// }
+///
+/// const V: Version = parse_version("1.22.3");
+/// assert_eq!(V, Version { major: 1, minor: 22, patch: 3 });
+/// ```
+impl<'a> ParseHelper<'a, [u8], Byte> {
+    /// `const` counterpart of [`leftover`](Self::leftover)
+    pub const fn const_leftover(&self) -> &'a [u8] {
+        match self.input.split_at_checked(self.byte_position) {
+            Some((_, rest)) => rest,
+            None => &[],
+        }
+    }
+
+    /// `const` counterpart of [`bytes_left`](Self::bytes_left)
+    pub const fn const_bytes_left(&self) -> usize {
+        self.const_leftover().len()
+    }
+
+    /// `const` counterpart of [`done`](Self::done)
+    pub const fn const_done(&self) -> bool {
+        self.const_bytes_left() == 0
+    }
+
+    /// `const` counterpart of [`upcoming_byte`](Self::upcoming_byte)
+    pub const fn const_upcoming_byte(&self) -> Option<u8> {
+        match self.const_leftover() {
+            [b, ..] => Some(*b),
+            [] => None,
+        }
+    }
+
+    /// `const` counterpart of [`accept_byte`](Self::accept_byte)
+    pub const fn const_accept_byte(&mut self, c: u8) -> bool {
+        self.const_accept_byte_in(&ByteSet::EMPTY.with(c)).is_some()
+    }
+
+    /// `const` counterpart of [`accept_byte_in`](Self::accept_byte_in)
+    pub const fn const_accept_byte_in(&mut self, set: &ByteSet) -> Option<u8> {
+        match self.const_upcoming_byte() {
+            Some(b) if set.contains(b) => {
+                self.advance(1);
+                Some(b)
+            }
+            _ => None,
+        }
+    }
+
+    /// `const` counterpart of [`accept`](Self::accept), for byte slices.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    ///
+    /// const MAGIC: bool = ParseHelper::new_byte_oriented(b"\x7fELF\x02".as_slice())
+    ///     .const_accept(b"\x7fELF")
+    ///     .is_some();
+    /// assert!(MAGIC);
+    /// ```
+    pub const fn const_accept(&mut self, bytes: &[u8]) -> Option<&'a [u8]> {
+        let Some((equivalent_input, _)) = self.const_leftover().split_at_checked(bytes.len())
+        else {
+            return None;
+        };
+
+        let (mut expected, mut found) = (bytes, equivalent_input);
+        while let ([a, expected_tail @ ..], [b, found_tail @ ..]) = (expected, found) {
+            if *a != *b {
+                return None;
+            }
+            expected = expected_tail;
+            found = found_tail;
+        }
+
+        self.advance(bytes.len());
+        Some(equivalent_input)
+    }
+
+    /// `const` counterpart of [`accept_until_byte_in`](Self::accept_until_byte_in)
+    pub const fn const_accept_until_byte_in(&mut self, set: &ByteSet) -> &'a [u8] {
+        let leftover = self.const_leftover();
+
+        let mut len: usize = 0;
+        let mut rest = leftover;
+        while let [b, tail @ ..] = rest {
+            if set.contains(*b) {
+                break;
+            }
+            len = len.saturating_add(1);
+            rest = tail;
+        }
+
+        self.advance(len);
+        match leftover.split_at_checked(len) {
+            Some((accepted, _)) => accepted,
+            None => &[],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Byte, ByteSet, ParseHelper};
+
+    // a fixed format id like `AB-1234`
+    const fn parse_id(s: &str) -> Option<([u8; 2], u16)> {
+        let mut ph = ParseHelper::new_byte_oriented(s.as_bytes());
+
+        let Some(a) = ph.const_accept_byte_in(&ByteSet::range(b'A', b'Z')) else {
+            return None;
+        };
+        let Some(b) = ph.const_accept_byte_in(&ByteSet::range(b'A', b'Z')) else {
+            return None;
+        };
+        if ph.const_accept(b"-").is_none() {
+            return None;
+        }
+
+        let mut n = 0u16;
+        while let Some(digit) = ph.const_accept_byte_in(&ByteSet::ASCII_DIGIT) {
+            n = n * 10 + (digit - b'0') as u16;
+        }
+
+        if ph.const_done() && ph.bytes_accepted() == 7 {
+            Some(([a, b], n))
+        } else {
+            None
+        }
+    }
+
+    const ID: Option<([u8; 2], u16)> = parse_id("XK-0042");
+
+    #[test]
+    fn const_ids() {
+        assert_eq!(ID, Some((*b"XK", 42)));
+        assert_eq!(parse_id("XK-042"), None);
+        assert_eq!(parse_id("Xk-0042"), None);
+        assert_eq!(parse_id("XK-00420"), None);
+    }
+
+    #[test]
+    fn same_as_runtime() {
+        let input = b"  abc def".as_slice();
+        let mut at_runtime = ParseHelper::new_byte_oriented(input);
+        let mut at_compile_time: ParseHelper<[u8], Byte> = ParseHelper::new_byte_oriented(input);
+
+        assert_eq!(
+            at_runtime.accept_until_byte_in(&ByteSet::ASCII_ALPHABETIC),
+            at_compile_time.const_accept_until_byte_in(&ByteSet::ASCII_ALPHABETIC)
+        );
+        assert_eq!(
+            at_runtime.accept(b"abd"),
+            at_compile_time.const_accept(b"abd")
+        );
+        assert_eq!(
+            at_runtime.accept(b"abc"),
+            at_compile_time.const_accept(b"abc")
+        );
+        assert_eq!(
+            at_runtime.accept(b" def!"),
+            at_compile_time.const_accept(b" def!")
+        );
+        assert_eq!(
+            at_runtime.upcoming_byte(),
+            at_compile_time.const_upcoming_byte()
+        );
+        assert_eq!(at_runtime.bytes_left(), at_compile_time.const_bytes_left());
+        assert_eq!(at_runtime.mark(), at_compile_time.mark());
+    }
+}
@@ -0,0 +1,523 @@
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::{iter::FusedIterator, slice};
+
+use crate::{Char, Mark, ParseHelper, Span, Spanned, SyntaxError};
+
+/// How a quote is written inside a quoted field.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CsvEscape {
+    /// By doubling it, like `"say ""hi"""`, as in RFC 4180.
+    DoubledQuote,
+    /// With a backslash, like `"say \"hi\""`. A backslash escapes any character after it,
+    /// so a backslash itself is written as `\\`.
+    Backslash,
+}
+
+/// The flavour of CSV to read with [`ParseHelper::csv_records`].
+///
+/// Records end at a line feed, optionally preceded by a carriage return.
+/// Empty lines are skipped.
+///
+/// ```
+/// use parse_helper::{CsvDialect, CsvEscape};
+///
+/// const SEMICOLONS: CsvDialect = CsvDialect::CSV
+///     .with_delimiter(';')
+///     .with_escape(CsvEscape::Backslash)
+///     .with_headers(true);
+///
+/// assert_eq!(SEMICOLONS.delimiter(), ';');
+/// assert_eq!(SEMICOLONS.quote(), Some('"'));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct CsvDialect {
+    delimiter: char,
+    quote: Option<char>,
+    escape: CsvEscape,
+    headers: bool,
+}
+
+impl CsvDialect {
+    /// Comma separated values as described by RFC 4180, without headers.
+    pub const CSV: Self = Self {
+        delimiter: ',',
+        quote: Some('"'),
+        escape: CsvEscape::DoubledQuote,
+        headers: false,
+    };
+    /// Like [`CSV`](Self::CSV), but separated by tabs.
+    pub const TSV: Self = Self::CSV.with_delimiter('\t');
+
+    /// Returns this dialect with fields separated by `delimiter`.
+    ///
+    /// The delimiter should differ from the quote, and can't be a line ending.
+    pub const fn with_delimiter(self, delimiter: char) -> Self {
+        Self { delimiter, ..self }
+    }
+
+    /// Returns this dialect with fields quoted by `quote`.
+    pub const fn with_quote(self, quote: char) -> Self {
+        Self {
+            quote: Some(quote),
+            ..self
+        }
+    }
+
+    /// Returns this dialect without quoted fields, so quotes are just part of a field.
+    pub const fn without_quote(self) -> Self {
+        Self {
+            quote: None,
+            ..self
+        }
+    }
+
+    /// Returns this dialect with quotes in quoted fields escaped as `escape`.
+    pub const fn with_escape(self, escape: CsvEscape) -> Self {
+        Self { escape, ..self }
+    }
+
+    /// Returns this dialect with or without a first record of headers,
+    /// see [`CsvRecords::headers`].
+    pub const fn with_headers(self, headers: bool) -> Self {
+        Self { headers, ..self }
+    }
+
+    /// The character between fields
+    pub const fn delimiter(&self) -> char {
+        self.delimiter
+    }
+
+    /// The character around quoted fields, if fields can be quoted
+    pub const fn quote(&self) -> Option<char> {
+        self.quote
+    }
+
+    /// How quotes are escaped in quoted fields
+    pub const fn escape(&self) -> CsvEscape {
+        self.escape
+    }
+
+    /// Whether the first record holds the headers
+    pub const fn has_headers(&self) -> bool {
+        self.headers
+    }
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self::CSV
+    }
+}
+
+/// Error returned when reading CSV fails.
+pub type CsvError = SyntaxError;
+
+/// A field of a [`CsvRecord`], with the span of the cell it was read from.
+///
+/// For quoted fields the span includes the quotes, and the value is only owned
+/// if it contained escapes.
+pub type CsvField<'a> = Spanned<Cow<'a, str>, Char>;
+
+/// A record read by [`CsvRecords`], which is a line of fields.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CsvRecord<'a> {
+    fields: Vec<CsvField<'a>>,
+    span: Span<Char>,
+}
+
+impl<'a> CsvRecord<'a> {
+    /// The span of the record, without its line ending.
+    pub fn span(&self) -> Span<Char> {
+        self.span
+    }
+
+    /// The number of fields, which is at least one.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Always returns `false`, because an empty line isn't a record.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns the field at `idx`.
+    pub fn get(&self, idx: usize) -> Option<&CsvField<'a>> {
+        self.fields.get(idx)
+    }
+
+    /// Returns the index of the first field with the value `value`,
+    /// like a column name in a header record.
+    pub fn position(&self, value: &str) -> Option<usize> {
+        self.fields.iter().position(|field| field.value == value)
+    }
+
+    /// Iterates over the fields.
+    pub fn iter(&self) -> slice::Iter<'_, CsvField<'a>> {
+        self.fields.iter()
+    }
+
+    /// Returns the fields.
+    pub fn into_fields(self) -> Vec<CsvField<'a>> {
+        self.fields
+    }
+}
+
+impl<'a> IntoIterator for CsvRecord<'a> {
+    type Item = CsvField<'a>;
+    type IntoIter = alloc::vec::IntoIter<CsvField<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fields.into_iter()
+    }
+}
+
+impl<'r, 'a> IntoIterator for &'r CsvRecord<'a> {
+    type Item = &'r CsvField<'a>;
+    type IntoIter = slice::Iter<'r, CsvField<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// how far the iterator got
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Headers,
+    Records,
+    Finished,
+}
+
+/// Iterator returned by [`ParseHelper::csv_records`].
+///
+/// After an error, the parse helper stays where the error was, and no more records are yielded.
+pub struct CsvRecords<'p, 'a, T: ?Sized> {
+    ph: &'p mut ParseHelper<'a, T, Char>,
+    dialect: CsvDialect,
+    headers: Option<CsvRecord<'a>>,
+    state: State,
+}
+
+impl<'a, T: ?Sized> CsvRecords<'_, 'a, T>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    /// Returns the headers, reading them first if that didn't happen yet.
+    ///
+    /// Returns `None` if the dialect has no headers, or if the input is empty.
+    ///
+    /// ```
+    /// use parse_helper::{CsvDialect, ParseHelper};
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("name,age\nAda,36\n");
+    /// let mut records = ph.csv_records(CsvDialect::CSV.with_headers(true));
+    ///
+    /// let age = records.headers().unwrap().unwrap().position("age").unwrap();
+    /// let ada = records.next().unwrap().unwrap();
+    /// assert_eq!(ada.get(age).unwrap().value, "36");
+    /// ```
+    pub fn headers(&mut self) -> Result<Option<&CsvRecord<'a>>, CsvError> {
+        if self.state == State::Headers {
+            self.state = State::Records;
+            match self.record() {
+                Ok(headers) => self.headers = headers,
+                Err(e) => {
+                    self.state = State::Finished;
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(self.headers.as_ref())
+    }
+
+    fn error(&self, byte_position: usize, message: &'static str) -> CsvError {
+        self.ph.syntax_error(byte_position, message)
+    }
+
+    fn accept_line_ending(&mut self) -> bool {
+        self.ph.accept("\r\n").is_some() || self.ph.accept_char('\n').is_some()
+    }
+
+    fn record(&mut self) -> Result<Option<CsvRecord<'a>>, CsvError> {
+        while self.accept_line_ending() {}
+        if self.ph.done() {
+            return Ok(None);
+        }
+
+        let start = self.ph.mark();
+        let mut fields = Vec::new();
+        loop {
+            fields.push(self.field()?);
+            if self.ph.accept_char(self.dialect.delimiter).is_some() {
+                continue;
+            }
+
+            let end = self.ph.mark();
+            if self.accept_line_ending() || self.ph.done() {
+                return Ok(Some(CsvRecord {
+                    fields,
+                    span: Span::new(start, end),
+                }));
+            }
+            return Err(self.error(
+                end.byte_position(),
+                "expected a delimiter or the end of the line",
+            ));
+        }
+    }
+
+    fn field(&mut self) -> Result<CsvField<'a>, CsvError> {
+        let start = self.ph.mark();
+        let value = match self.dialect.quote {
+            Some(quote) if self.ph.accept_char(quote).is_some() => self.quoted(quote, start)?,
+            quote => self.unquoted(quote)?,
+        };
+
+        Ok(Spanned::new(value, Span::new(start, self.ph.mark())))
+    }
+
+    fn unquoted(&mut self, quote: Option<char>) -> Result<Cow<'a, str>, CsvError> {
+        let rest = self.ph.leftover();
+        let end = rest
+            .char_indices()
+            .find(|&(idx, c)| {
+                c == self.dialect.delimiter
+                    || c == '\n'
+                    || Some(c) == quote
+                    || (c == '\r' && rest.get(idx..).is_some_and(|r| r.starts_with("\r\n")))
+            })
+            .map_or(rest.len(), |(idx, _)| idx);
+
+        let value = rest.get(..end).unwrap_or_default();
+        self.ph.advance(end);
+        if quote.is_some() && self.ph.upcoming_char() == quote {
+            return Err(self.error(self.ph.byte_position, "quote in an unquoted field"));
+        }
+
+        Ok(Cow::Borrowed(value))
+    }
+
+    fn quoted(&mut self, quote: char, start: Mark<Char>) -> Result<Cow<'a, str>, CsvError> {
+        let content = self.ph.leftover();
+        // only allocated once an escape is found
+        let mut owned: Option<String> = None;
+        let unescaped_until = |idx: usize| String::from(content.get(..idx).unwrap_or_default());
+
+        let mut chars = content.char_indices().peekable();
+        while let Some((idx, c)) = chars.next() {
+            match (c, self.dialect.escape) {
+                (c, CsvEscape::DoubledQuote) if c == quote => {
+                    if chars.next_if(|&(_, next)| next == quote).is_some() {
+                        owned
+                            .get_or_insert_with(|| unescaped_until(idx))
+                            .push(quote);
+                        continue;
+                    }
+                }
+                (c, CsvEscape::Backslash) if c == quote => {}
+                ('\\', CsvEscape::Backslash) => {
+                    let owned = owned.get_or_insert_with(|| unescaped_until(idx));
+                    match chars.next() {
+                        Some((_, escaped)) => owned.push(escaped),
+                        None => break,
+                    }
+                    continue;
+                }
+                (c, _) => {
+                    if let Some(owned) = &mut owned {
+                        owned.push(c);
+                    }
+                    continue;
+                }
+            }
+
+            // the closing quote
+            self.ph.advance(idx.saturating_add(quote.len_utf8()));
+            return Ok(match owned {
+                Some(owned) => Cow::Owned(owned),
+                None => Cow::Borrowed(content.get(..idx).unwrap_or_default()),
+            });
+        }
+
+        Err(self.error(start.byte_position(), "unterminated quoted field"))
+    }
+}
+
+impl<'a, T: ?Sized> Iterator for CsvRecords<'_, 'a, T>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    type Item = Result<CsvRecord<'a>, CsvError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Err(e) = self.headers() {
+            return Some(Err(e));
+        }
+        if self.state == State::Finished {
+            return None;
+        }
+
+        let res = self.record().transpose();
+        if !matches!(res, Some(Ok(_))) {
+            self.state = State::Finished;
+        }
+        res
+    }
+}
+
+impl<T: ?Sized> FusedIterator for CsvRecords<'_, '_, T> where T: AsRef<str> + AsRef<[u8]> {}
+
+impl<'a, T: ?Sized> ParseHelper<'a, T, Char>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    /// Returns an iterator over the records in the rest of the input, read as CSV in
+    /// the given dialect.
+    ///
+    /// Every record is accepted as it is yielded, together with its line ending.
+    ///
+    /// ```
+    /// use parse_helper::{CsvDialect, ParseHelper};
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("id,comment\r\n1,\"said \"\"hi\"\"\"\r\n2,\r\n");
+    /// let records: Vec<_> = ph
+    ///     .csv_records(CsvDialect::CSV)
+    ///     .collect::<Result<_, _>>()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(records.len(), 3);
+    /// let comment = records[1].get(1).unwrap();
+    /// assert_eq!(comment.value, r#"said "hi""#);
+    /// assert_eq!(ph.slice(comment.span), r#""said ""hi""""#);
+    /// assert_eq!(records[2].get(1).unwrap().value, "");
+    /// ```
+    ///
+    /// Errors point at where reading went wrong, so problems with the values of fields
+    /// can be reported at their exact cell too:
+    ///
+    /// ```
+    /// use parse_helper::{CsvDialect, ParseHelper};
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("a,\"b\"c\n");
+    /// let err = ph.csv_records(CsvDialect::CSV).next().unwrap().unwrap_err();
+    ///
+    /// assert_eq!(err.message(), "expected a delimiter or the end of the line");
+    /// assert_eq!(err.mark().byte_position(), 5);
+    /// ```
+    pub fn csv_records(&mut self, dialect: CsvDialect) -> CsvRecords<'_, 'a, T> {
+        CsvRecords {
+            ph: self,
+            dialect,
+            headers: None,
+            state: match dialect.headers {
+                true => State::Headers,
+                false => State::Records,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::Cow;
+
+    use crate::{CsvDialect, CsvEscape, CsvRecord, ParseHelper};
+
+    fn values(record: &CsvRecord) -> Vec<String> {
+        record.iter().map(|field| field.value.to_string()).collect()
+    }
+
+    fn read(input: &str, dialect: CsvDialect) -> Vec<Vec<String>> {
+        let mut ph = ParseHelper::new_char_oriented(input);
+        let records = ph
+            .csv_records(dialect)
+            .map(|record| values(&record.unwrap()))
+            .collect();
+        assert!(ph.done());
+        records
+    }
+
+    #[test]
+    fn line_endings() {
+        let expected = [["a", "b"], ["c", ""]];
+        for input in ["a,b\nc,", "a,b\r\nc,\r\n", "\na,b\n\n\r\nc,\n\n"] {
+            assert_eq!(read(input, CsvDialect::CSV), expected, "{input:?}");
+        }
+
+        // a carriage return on its own is part of a field
+        assert_eq!(read("a\rb,\"c\r\"\r\n", CsvDialect::CSV), [["a\rb", "c\r"]]);
+        assert!(read("", CsvDialect::CSV).is_empty());
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(
+            read("\"a,b\",\"\",\"x\ny\"", CsvDialect::CSV),
+            [["a,b", "", "x\ny"]]
+        );
+
+        let backslash = CsvDialect::CSV.with_escape(CsvEscape::Backslash);
+        assert_eq!(
+            read(r#""say \"hi\"","\\",a\b"#, backslash),
+            [["say \"hi\"", "\\", "a\\b"]]
+        );
+
+        let single = CsvDialect::TSV.with_quote('\'');
+        assert_eq!(read("'a''b'\t\"c\"", single), [["a'b", "\"c\""]]);
+
+        let unquoted = CsvDialect::CSV.without_quote();
+        assert_eq!(read("\"a,b\"", unquoted), [["\"a", "b\""]]);
+
+        let mut ph = ParseHelper::new_char_oriented("\"plain\",\"a\"\"b\"");
+        let record = ph.csv_records(CsvDialect::CSV).next().unwrap().unwrap();
+        assert!(matches!(
+            record.get(0).unwrap().value,
+            Cow::Borrowed("plain")
+        ));
+        assert!(matches!(record.get(1).unwrap().value, Cow::Owned(_)));
+    }
+
+    #[test]
+    fn errors() {
+        for (input, message, position) in [
+            ("a,\"bc", "unterminated quoted field", 2),
+            ("ok\nab\"c\"", "quote in an unquoted field", 5),
+            ("\"a\" ,b", "expected a delimiter or the end of the line", 3),
+            ("\"a\"\r", "expected a delimiter or the end of the line", 3),
+        ] {
+            let mut ph = ParseHelper::new_char_oriented(input);
+            let mut records = ph.csv_records(CsvDialect::CSV);
+            let err = records.find_map(Result::err).unwrap();
+
+            assert_eq!(err.message(), message, "{input:?}");
+            assert_eq!(err.mark().byte_position(), position, "{input:?}");
+            assert!(records.next().is_none());
+        }
+    }
+
+    #[test]
+    fn headers_and_spans() {
+        let input = "name;city\nAda;London\n\"Grace\";Arlington";
+        let mut ph = ParseHelper::new_char_oriented(input);
+        let mut records = ph.csv_records(CsvDialect::CSV.with_delimiter(';').with_headers(true));
+
+        let records_read: Vec<_> = records.by_ref().map(Result::unwrap).collect();
+        let headers = records.headers().unwrap().unwrap().clone();
+        assert_eq!(values(&headers), ["name", "city"]);
+        assert_eq!(records_read.len(), 2);
+
+        let city = headers.position("city").unwrap();
+        let grace = &records_read[1];
+        assert_eq!(ph.slice(grace.span()), "\"Grace\";Arlington");
+        assert_eq!(ph.slice(grace.get(0).unwrap().span), "\"Grace\"");
+        assert_eq!(ph.slice(grace.get(city).unwrap().span), "Arlington");
+        assert_eq!(headers.position("age"), None);
+
+        let mut ph = ParseHelper::new_char_oriented("\n\n");
+        let mut records = ph.csv_records(CsvDialect::TSV.with_headers(true));
+        assert_eq!(records.headers(), Ok(None));
+        assert!(records.next().is_none());
+    }
+}
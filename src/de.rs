@@ -0,0 +1,529 @@
+use alloc::{
+    borrow::{Cow, ToOwned},
+    string::{String, ToString},
+};
+use core::fmt::{self, Display, Formatter};
+
+use serde::{
+    de::{self, DeserializeSeed, IntoDeserializer, Unexpected, Visitor},
+    forward_to_deserialize_any, Deserialize,
+};
+
+use crate::{Char, LineColumn, Mark, ParseHelper, SyntaxError};
+
+/// Error returned when deserializing with [`ParseHelper::deserialize`] fails.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DeserializeError {
+    message: String,
+    // errors made by `de::Error::custom` don't know where they happened,
+    // the deserializer fills this in when they pass through it
+    line_column: Option<LineColumn>,
+}
+
+impl DeserializeError {
+    /// What went wrong.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Where in the input it went wrong.
+    pub fn line_column(&self) -> LineColumn {
+        self.line_column
+            .unwrap_or(LineColumn { line: 1, column: 1 })
+    }
+
+    fn or_at(mut self, line_column: LineColumn) -> Self {
+        self.line_column.get_or_insert(line_column);
+        self
+    }
+}
+
+impl Display for DeserializeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let LineColumn { line, column } = self.line_column();
+        write!(f, "{} at line {line} column {column}", self.message)
+    }
+}
+
+// this is `std::error::Error` when serde's std feature is enabled
+impl de::StdError for DeserializeError {}
+
+impl de::Error for DeserializeError {
+    fn custom<M: Display>(msg: M) -> Self {
+        Self {
+            message: msg.to_string(),
+            line_column: None,
+        }
+    }
+}
+
+type Result<V> = core::result::Result<V, DeserializeError>;
+
+impl<'a, T: ?Sized> ParseHelper<'a, T, Char>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    /// Deserializes a value written in a JSON-compatible format, and the whitespace around it.
+    ///
+    /// Strings without escapes are borrowed from the input, so they can be deserialized as
+    /// `&'a str`. Returns an error without accepting anything when it fails.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Debug, PartialEq)]
+    /// struct Config<'a> {
+    ///     name: &'a str,
+    ///     port: u16,
+    ///     tags: Vec<String>,
+    /// }
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented(r#"{
+    ///     "name": "server",
+    ///     "port": 8080,
+    ///     "tags": ["a", "b\n"]
+    /// }"#);
+    /// let config: Config = ph.deserialize().unwrap();
+    /// assert_eq!(config.name, "server");
+    /// assert_eq!(config.tags, ["a", "b\n"]);
+    /// assert!(ph.done());
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("{\n  \"name\": 1\n}");
+    /// let err = ph.deserialize::<Config>().unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "invalid type: integer `1`, expected a borrowed string at line 2 column 11"
+    /// );
+    /// ```
+    pub fn deserialize<D: Deserialize<'a>>(&mut self) -> Result<D> {
+        let old = self.create_backup();
+
+        let res = D::deserialize(&mut Deserializer::new(self));
+        match res {
+            Ok(_) => {
+                self.accept_zero_or_more_whitespace();
+            }
+            Err(_) => self.restore_backup(old),
+        }
+
+        res
+    }
+}
+
+/// A serde deserializer for a JSON-compatible format, reading from a [`ParseHelper`].
+///
+/// Most of the time [`ParseHelper::deserialize`] is easier to use.
+pub struct Deserializer<'p, 'a, T: ?Sized> {
+    ph: &'p mut ParseHelper<'a, T, Char>,
+}
+
+impl<'p, 'a, T: ?Sized> Deserializer<'p, 'a, T>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    /// Creates a deserializer that reads from the current position of a parse helper.
+    pub fn new(ph: &'p mut ParseHelper<'a, T, Char>) -> Self {
+        Self { ph }
+    }
+
+    fn error_at(&self, mark: Mark<Char>, message: &str) -> DeserializeError {
+        DeserializeError {
+            message: message.to_owned(),
+            line_column: Some(self.ph.line_column(mark)),
+        }
+    }
+
+    // a syntax error at the current position
+    fn error(&self, expected: &str) -> DeserializeError {
+        let mark = self.ph.mark();
+        match self.ph.upcoming_char() {
+            Some(_) => self.error_at(mark, expected),
+            None => self.error_at(mark, "unexpected end of input"),
+        }
+    }
+
+    fn syntax_error(&self, error: SyntaxError) -> DeserializeError {
+        DeserializeError {
+            message: error.message().to_owned(),
+            line_column: Some(error.line_column()),
+        }
+    }
+
+    fn whitespace(&mut self) {
+        self.ph.accept_zero_or_more_whitespace();
+    }
+
+    fn expect_char(&mut self, c: char, expected: &str) -> Result<()> {
+        match self.ph.accept_char(c) {
+            Some(_) => Ok(()),
+            None => Err(self.error(expected)),
+        }
+    }
+
+    // runs `f` one level deeper, so hostile input can't overflow the stack
+    fn nested<R>(
+        &mut self,
+        f: impl FnOnce(&mut Deserializer<'_, 'a, T>) -> Result<R>,
+    ) -> Result<R> {
+        match self.ph.nested(|ph| f(&mut Deserializer { ph })) {
+            Ok(res) => res,
+            Err(e) => Err(self.error_at(e.mark(), "nested too deeply")),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<Cow<'a, str>> {
+        if self.ph.upcoming_char() != Some('"') {
+            return Err(self.error("expected a string"));
+        }
+        match self.ph.json_string() {
+            Ok(s) => Ok(s.unescape()),
+            Err(e) => Err(self.syntax_error(e)),
+        }
+    }
+
+    fn parse_number<V: Visitor<'a>>(&mut self, visitor: V) -> Result<V::Value> {
+        let start = self.ph.mark();
+        let number = match self.ph.json_number() {
+            Ok(number) => number,
+            Err(e) => return Err(self.syntax_error(e)),
+        };
+
+        // integers that don't fit are deserialized as floats, like most JSON parsers do
+        if let Some(n) = number.as_u64() {
+            visitor.visit_u64(n)
+        } else if let Some(n) = number.as_i64() {
+            visitor.visit_i64(n)
+        } else if let Some(n) = number.as_f64() {
+            visitor.visit_f64(n)
+        } else {
+            Err(self.error_at(start, "invalid number"))
+        }
+    }
+
+    fn parse_any<V: Visitor<'a>>(&mut self, visitor: V) -> Result<V::Value> {
+        match self.ph.upcoming_char() {
+            Some('n') if self.ph.accept("null").is_some() => visitor.visit_unit(),
+            Some('t') if self.ph.accept("true").is_some() => visitor.visit_bool(true),
+            Some('f') if self.ph.accept("false").is_some() => visitor.visit_bool(false),
+            Some('"') => match self.parse_string()? {
+                Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+                Cow::Owned(s) => visitor.visit_string(s),
+            },
+            Some('-' | '0'..='9') => self.parse_number(visitor),
+            Some('[') => {
+                self.ph.accept_char('[');
+                let res = self.nested(|de| visitor.visit_seq(Sequence { de, first: true }))?;
+                self.whitespace();
+                self.expect_char(']', "expected `,` or `]`")?;
+                Ok(res)
+            }
+            Some('{') => {
+                self.ph.accept_char('{');
+                let res = self.nested(|de| visitor.visit_map(Sequence { de, first: true }))?;
+                self.whitespace();
+                self.expect_char('}', "expected `,` or `}`")?;
+                Ok(res)
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    // parses with `f` after skipping whitespace, and gives errors without a position the
+    // position of the value
+    fn value<R>(&mut self, f: impl FnOnce(&mut Self) -> Result<R>) -> Result<R> {
+        self.whitespace();
+        let start = self.ph.line_column(self.ph.mark());
+        f(self).map_err(|e| e.or_at(start))
+    }
+}
+
+impl<'de, T: ?Sized> de::Deserializer<'de> for &mut Deserializer<'_, 'de, T>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    type Error = DeserializeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.value(|de| de.parse_any(visitor))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.value(|de| match de.ph.accept("null") {
+            Some(_) => visitor.visit_none(),
+            None => visitor.visit_some(de),
+        })
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.value(|de| visitor.visit_newtype_struct(de))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.value(|de| {
+            // unit variants are strings, other variants are objects with a single field
+            if de.ph.accept_char('{').is_none() {
+                return visitor.visit_enum(Enum { de, braced: false });
+            }
+
+            let res = de.nested(|de| visitor.visit_enum(Enum { de, braced: true }))?;
+            de.whitespace();
+            de.expect_char('}', "expected `}`")?;
+            Ok(res)
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+// the elements of an array, or the entries of an object
+struct Sequence<'d, 'p, 'a, T: ?Sized> {
+    de: &'d mut Deserializer<'p, 'a, T>,
+    first: bool,
+}
+
+impl<T: ?Sized> Sequence<'_, '_, '_, T>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    // returns whether there is another element before `close`
+    fn has_next(&mut self, close: char) -> Result<bool> {
+        self.de.whitespace();
+        if self.de.ph.upcoming_char() == Some(close) {
+            return Ok(false);
+        }
+
+        if !self.first {
+            let expected = if close == ']' {
+                "expected `,` or `]`"
+            } else {
+                "expected `,` or `}`"
+            };
+            self.de.expect_char(',', expected)?;
+            self.de.whitespace();
+        }
+        self.first = false;
+        Ok(true)
+    }
+}
+
+impl<'de, T: ?Sized> de::SeqAccess<'de> for Sequence<'_, '_, 'de, T>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    type Error = DeserializeError;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<Option<S::Value>> {
+        if !self.has_next(']')? {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+impl<'de, T: ?Sized> de::MapAccess<'de> for Sequence<'_, '_, 'de, T>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    type Error = DeserializeError;
+
+    fn next_key_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<Option<S::Value>> {
+        if !self.has_next('}')? {
+            return Ok(None);
+        }
+        // keys are always strings
+        if self.de.ph.upcoming_char() != Some('"') {
+            return Err(self.de.error("expected a string"));
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value> {
+        self.de.whitespace();
+        self.de.expect_char(':', "expected `:`")?;
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+// a variant of an enum, either as a string or as an object with a single field
+struct Enum<'d, 'p, 'a, T: ?Sized> {
+    de: &'d mut Deserializer<'p, 'a, T>,
+    braced: bool,
+}
+
+impl<'d, 'p, 'de, T: ?Sized> de::EnumAccess<'de> for Enum<'d, 'p, 'de, T>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    type Error = DeserializeError;
+    type Variant = Self;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<(S::Value, Self)> {
+        self.de.whitespace();
+        let name = self.de.parse_string()?;
+        let variant = match name {
+            Cow::Borrowed(s) => seed.deserialize(s.into_deserializer()),
+            Cow::Owned(s) => seed.deserialize(s.into_deserializer()),
+        }?;
+
+        if self.braced {
+            self.de.whitespace();
+            self.de.expect_char(':', "expected `:`")?;
+        }
+        Ok((variant, self))
+    }
+}
+
+impl<'de, T: ?Sized> de::VariantAccess<'de> for Enum<'_, '_, 'de, T>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    type Error = DeserializeError;
+
+    fn unit_variant(self) -> Result<()> {
+        if self.braced {
+            de::Deserialize::deserialize(&mut *self.de)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<S::Value> {
+        if !self.braced {
+            return Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"newtype variant",
+            ));
+        }
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        if !self.braced {
+            return Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"tuple variant",
+            ));
+        }
+        de::Deserializer::deserialize_seq(&mut *self.de, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        if !self.braced {
+            return Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"struct variant",
+            ));
+        }
+        de::Deserializer::deserialize_map(&mut *self.de, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{borrow::Cow, collections::BTreeMap, string::String, vec, vec::Vec};
+
+    use serde::Deserialize;
+
+    use crate::{LineColumn, ParseHelper};
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Point(i32, i32),
+        Rect { w: u32, h: u32 },
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Document<'a> {
+        title: &'a str,
+        #[serde(borrow)]
+        body: Cow<'a, str>,
+        shapes: Vec<Shape>,
+        extra: Option<BTreeMap<String, i64>>,
+        missing: Option<bool>,
+    }
+
+    #[test]
+    fn document() {
+        let input = r#" {
+            "title": "shapes",
+            "body": "tab\there \u00e9 \ud83e\udd80",
+            "shapes": ["Empty", {"Circle": 1.5e1}, {"Point": [-1, 2]}, {"Rect": {"w": 3, "h": 4}}],
+            "extra": {"a": -9223372036854775808, "b": 0},
+            "missing": null,
+            "ignored": [true, false, {}, [], 0.5]
+        } rest"#;
+
+        let mut ph = ParseHelper::new_char_oriented(input);
+        let doc: Document = ph.deserialize().unwrap();
+
+        assert_eq!(
+            doc,
+            Document {
+                title: "shapes",
+                body: Cow::Owned("tab\there é 🦀".into()),
+                shapes: vec![
+                    Shape::Empty,
+                    Shape::Circle(15.0),
+                    Shape::Point(-1, 2),
+                    Shape::Rect { w: 3, h: 4 }
+                ],
+                extra: Some([("a".into(), i64::MIN), ("b".into(), 0)].into()),
+                missing: None,
+            }
+        );
+        assert_eq!(ph.leftover(), "rest");
+    }
+
+    #[test]
+    fn errors() {
+        let cases = [
+            ("[1, 2", "unexpected end of input", 1, 6),
+            ("[1 2]", "expected `,` or `]`", 1, 4),
+            ("{\"a\" 1}", "expected `:`", 1, 6),
+            ("{1: 2}", "expected a string", 1, 2),
+            ("\"ab", "unterminated string", 1, 1),
+            ("\"a\\x\"", "invalid escape", 1, 3),
+            ("\"\\ud800\"", "invalid escape", 1, 2),
+            ("\n  -", "invalid number", 2, 3),
+            ("[01]", "expected `,` or `]`", 1, 3),
+            ("nul", "expected a value", 1, 1),
+        ];
+
+        for (input, message, line, column) in cases {
+            let mut ph = ParseHelper::new_char_oriented(input);
+            let err = ph.deserialize::<serde::de::IgnoredAny>().unwrap_err();
+            assert_eq!(err.message(), message, "{input}");
+            assert_eq!(err.line_column(), LineColumn { line, column }, "{input}");
+            assert_eq!(ph.bytes_accepted(), 0);
+        }
+
+        let mut ph = ParseHelper::new_char_oriented("[\n  1,\n  \"x\"\n]");
+        let err = ph.deserialize::<Vec<u8>>().unwrap_err();
+        assert_eq!(err.line_column(), LineColumn { line: 3, column: 3 });
+
+        let input = "[".repeat(10_000);
+        let mut ph = ParseHelper::new_char_oriented(input.as_str());
+        let err = ph.deserialize::<serde::de::IgnoredAny>().unwrap_err();
+        assert_eq!(err.message(), "nested too deeply");
+    }
+}
@@ -0,0 +1,526 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Write;
+
+use crate::{ParseHelper, Span};
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Severity {
+    /// Something went wrong, and parsing could not succeed.
+    Error,
+    /// Parsing succeeded, but something looks suspicious.
+    Warning,
+    /// Extra information.
+    Note,
+    /// A suggestion on how to fix a problem.
+    Help,
+}
+
+impl Severity {
+    fn name(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+            Severity::Help => "help",
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+            Severity::Note => GREEN,
+            Severity::Help => CYAN,
+        }
+    }
+}
+
+// ANSI escape codes used by `Diagnostic::render_ansi`
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const GREEN: &str = "\x1b[1;32m";
+const CYAN: &str = "\x1b[1;36m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// The input a [`Diagnostic`] is rendered against, together with a name to show for it
+/// (usually a file name).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Source<'a> {
+    name: &'a str,
+    text: &'a str,
+}
+
+impl<'a> Source<'a> {
+    /// Creates a source from a name and the text that was parsed.
+    pub fn new(name: &'a str, text: &'a str) -> Self {
+        Self { name, text }
+    }
+}
+
+impl<'a, T: ?Sized, B> ParseHelper<'a, T, B>
+where
+    T: AsRef<str>,
+{
+    /// Creates a [`Source`] from the input of this parse helper, to render [`Diagnostic`]s with.
+    pub fn source(&self, name: &'a str) -> Source<'a> {
+        Source::new(name, self.input.as_ref())
+    }
+}
+
+/// Points out a [`Span`] of the input in a [`Diagnostic`], together with a message.
+///
+/// Primary labels are underlined with `^` and point out the problem itself,
+/// secondary labels are underlined with `-` and give context.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Label<B> {
+    span: Span<B>,
+    message: String,
+    primary: bool,
+}
+
+impl<B> Label<B> {
+    /// A label pointing out the problem itself. A [`Mark`](crate::Mark) can be given to point
+    /// at a single position.
+    pub fn primary(span: impl Into<Span<B>>, message: impl Into<String>) -> Self {
+        Self {
+            span: span.into(),
+            message: message.into(),
+            primary: true,
+        }
+    }
+
+    /// A label giving context to the problem. A [`Mark`](crate::Mark) can be given to point
+    /// at a single position.
+    pub fn secondary(span: impl Into<Span<B>>, message: impl Into<String>) -> Self {
+        Self {
+            span: span.into(),
+            message: message.into(),
+            primary: false,
+        }
+    }
+}
+
+/// A message about the input, that can be rendered like rustc does:
+///
+/// ```
+/// use parse_helper::{Diagnostic, Label, ParseHelper};
+///
+/// let mut ph = ParseHelper::new_char_oriented("let x = 5\nlet y = 6;\n");
+///
+/// let ((), x) = ph.span_of(|ph| { ph.accept_until_char('\n'); });
+/// let end_of_line = ph.mark();
+///
+/// let diagnostic = Diagnostic::error("expected `;`")
+///     .with_label(Label::primary(end_of_line, "add `;` here"))
+///     .with_label(Label::secondary(x, "in this statement"))
+///     .with_help("statements end in a semicolon");
+///
+/// assert_eq!(diagnostic.render(&ph.source("main.rs")), "\
+/// error: expected `;`
+///  --> main.rs:1:10
+///   |
+/// 1 | let x = 5
+///   | --------- in this statement
+///   |          ^ add `;` here
+///   |
+///   = help: statements end in a semicolon
+/// ");
+/// ```
+///
+/// Columns are measured in display width, so labels line up under wide characters too.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Diagnostic<B> {
+    severity: Severity,
+    message: String,
+    labels: Vec<Label<B>>,
+    // notes and help messages, in the order they were added
+    footer: Vec<(Severity, String)>,
+}
+
+impl<B> Diagnostic<B> {
+    /// Creates a diagnostic without any labels.
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            labels: Vec::new(),
+            footer: Vec::new(),
+        }
+    }
+
+    /// Creates an error diagnostic without any labels.
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    /// Creates a warning diagnostic without any labels.
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    /// Adds a label pointing out part of the input.
+    pub fn with_label(mut self, label: Label<B>) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    /// Adds a note at the bottom of the diagnostic.
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.footer.push((Severity::Note, note.into()));
+        self
+    }
+
+    /// Adds a help message at the bottom of the diagnostic.
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.footer.push((Severity::Help, help.into()));
+        self
+    }
+
+    /// How serious this diagnostic is.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// The main message of this diagnostic.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// All labels added to this diagnostic.
+    pub fn labels(&self) -> &[Label<B>] {
+        &self.labels
+    }
+}
+
+impl<B: Copy> Diagnostic<B> {
+    /// Renders this diagnostic as plain text.
+    pub fn render(&self, source: &Source<'_>) -> String {
+        self.render_with(source, false)
+    }
+
+    /// Renders this diagnostic as text coloured with ANSI escape codes, for terminals.
+    pub fn render_ansi(&self, source: &Source<'_>) -> String {
+        self.render_with(source, true)
+    }
+
+    fn render_with(&self, source: &Source<'_>, colors: bool) -> String {
+        let mut out = Painter {
+            out: String::new(),
+            colors,
+        };
+
+        out.paint(self.severity.color(), self.severity.name());
+        out.paint(BOLD, &[": ", &self.message].concat());
+        out.newline();
+
+        let mut labels: Vec<_> = self
+            .labels
+            .iter()
+            .map(|label| ResolvedLabel::new(source.text, label))
+            .collect();
+        labels.sort_by_key(|label| (label.line, label.start_col, !label.primary));
+
+        let gutter = labels
+            .last()
+            .map_or(0, |label| label.line.to_string().len());
+        let empty_gutter = " ".repeat(gutter);
+
+        if let Some(location) = labels.iter().find(|l| l.primary).or(labels.first()) {
+            out.plain(&empty_gutter);
+            out.paint(BLUE, "--> ");
+            out.plain(&format!(
+                "{}:{}:{}",
+                source.name, location.line, location.column
+            ));
+            out.newline();
+
+            out.gutter(&empty_gutter);
+            out.newline();
+        }
+
+        let mut previous_line = None;
+        for (idx, label) in labels.iter().enumerate() {
+            if previous_line != Some(label.line) {
+                if previous_line.is_some_and(|prev: usize| prev.saturating_add(1) < label.line) {
+                    out.paint(BLUE, "...");
+                    out.newline();
+                }
+
+                out.gutter(&format!("{:>gutter$}", label.line));
+                out.plain(" ");
+                out.plain(&expand_tabs(label.source_line));
+                out.newline();
+                previous_line = Some(label.line);
+            }
+
+            let (color, mark) = if label.primary {
+                (self.severity.color(), "^")
+            } else {
+                (BLUE, "-")
+            };
+
+            out.gutter(&empty_gutter);
+            out.plain(" ");
+            out.plain(&" ".repeat(label.start_col));
+            out.paint(
+                color,
+                &mark.repeat(label.end_col.saturating_sub(label.start_col)),
+            );
+            if !label.message.is_empty() {
+                out.paint(color, &[" ", label.message].concat());
+            }
+            out.newline();
+
+            // separate labels from the footer
+            if idx.saturating_add(1) == labels.len() && !self.footer.is_empty() {
+                out.gutter(&empty_gutter);
+                out.newline();
+            }
+        }
+
+        for (severity, message) in &self.footer {
+            out.plain(&empty_gutter);
+            out.paint(BLUE, " = ");
+            out.paint(BOLD, severity.name());
+            out.plain(&[": ", message].concat());
+            out.newline();
+        }
+
+        out.out
+    }
+}
+
+struct Painter {
+    out: String,
+    colors: bool,
+}
+
+impl Painter {
+    fn plain(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+
+    fn paint(&mut self, color: &str, text: &str) {
+        if self.colors {
+            let _ = write!(self.out, "{color}{text}{RESET}");
+        } else {
+            self.plain(text);
+        }
+    }
+
+    fn gutter(&mut self, line_number: &str) {
+        self.paint(BLUE, &[line_number, " |"].concat());
+    }
+
+    fn newline(&mut self) {
+        // don't leave trailing whitespace behind
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+        self.out.push('\n');
+    }
+}
+
+// a label, resolved to where in the text it should be drawn
+struct ResolvedLabel<'a> {
+    line: usize,
+    column: usize,
+    source_line: &'a str,
+    // in display width, relative to the start of the line
+    start_col: usize,
+    end_col: usize,
+    message: &'a str,
+    primary: bool,
+}
+
+impl<'a> ResolvedLabel<'a> {
+    fn new<B: Copy>(text: &'a str, label: &'a Label<B>) -> Self {
+        let start = clamp_to_char_boundary(text, label.span.start().byte_position());
+        let end = clamp_to_char_boundary(text, label.span.end().byte_position()).max(start);
+
+        let before = &text[..start];
+        let line_start = before.rfind('\n').map_or(0, |idx| idx.saturating_add(1));
+        let line_end = text[start..]
+            .find('\n')
+            .map_or(text.len(), |idx| idx.saturating_add(start));
+        let source_line = text[line_start..line_end].trim_end_matches('\r');
+
+        // spans that continue on the next line are underlined until the end of the first line
+        let end_on_line = end
+            .min(line_start.saturating_add(source_line.len()))
+            .max(start);
+        let start_col = display_width(&text[line_start..start]);
+        let end_col = start_col.saturating_add(display_width(&text[start..end_on_line]).max(1));
+
+        Self {
+            line: before.matches('\n').count().saturating_add(1),
+            column: before[line_start..].chars().count().saturating_add(1),
+            source_line,
+            start_col,
+            end_col,
+            message: &label.message,
+            primary: label.primary,
+        }
+    }
+}
+
+fn clamp_to_char_boundary(text: &str, mut position: usize) -> usize {
+    position = position.min(text.len());
+    while !text.is_char_boundary(position) {
+        position = position.saturating_sub(1);
+    }
+    position
+}
+
+fn expand_tabs(line: &str) -> String {
+    line.replace('\t', "    ")
+}
+
+fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+// how many columns a character takes up in a terminal
+#[cfg(feature = "icu")]
+fn char_width(c: char) -> usize {
+    use icu_properties::{maps, EastAsianWidth, GeneralCategory};
+
+    if c == '\t' {
+        return 4;
+    }
+
+    match maps::general_category().get(c) {
+        GeneralCategory::NonspacingMark
+        | GeneralCategory::EnclosingMark
+        | GeneralCategory::Format => return 0,
+        _ => {}
+    }
+
+    match maps::east_asian_width().get(c) {
+        EastAsianWidth::Wide | EastAsianWidth::Fullwidth => 2,
+        _ => 1,
+    }
+}
+
+// how many columns a character takes up in a terminal. Without the unicode tables of `icu`,
+// this covers the most common zero width and wide ranges.
+#[cfg(not(feature = "icu"))]
+fn char_width(c: char) -> usize {
+    match c {
+        '\t' => 4,
+        '\u{0300}'..='\u{036f}'
+        | '\u{200b}'..='\u{200f}'
+        | '\u{20d0}'..='\u{20ff}'
+        | '\u{fe00}'..='\u{fe0f}' => 0,
+        '\u{1100}'..='\u{115f}'
+        | '\u{2e80}'..='\u{303e}'
+        | '\u{3041}'..='\u{33ff}'
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{a000}'..='\u{a4cf}'
+        | '\u{ac00}'..='\u{d7a3}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{fe30}'..='\u{fe4f}'
+        | '\u{ff00}'..='\u{ff60}'
+        | '\u{ffe0}'..='\u{ffe6}'
+        | '\u{1f300}'..='\u{1f64f}'
+        | '\u{1f900}'..='\u{1f9ff}'
+        | '\u{20000}'..='\u{3fffd}' => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Byte, Diagnostic, Label, ParseHelper, Source};
+
+    #[test]
+    fn render_multiple_lines() {
+        let text = "fn main() {\n    foo(\n\n\n    bar)\n}\n";
+        let mut ph = ParseHelper::new_byte_oriented(text);
+
+        ph.accept_until_byte(b'{');
+        ph.accept_until_byte(b'(');
+        let open = ph.mark();
+        ph.accept_until_byte(b'b');
+        let ((), bar) = ph.span_of(|ph| {
+            ph.accept_until_byte(b')');
+        });
+
+        let diagnostic = Diagnostic::<Byte>::error("unknown value")
+            .with_label(Label::primary(bar, "not found in this scope"))
+            .with_label(Label::secondary(open, "in this call"))
+            .with_note("a note")
+            .with_help("some help");
+
+        assert_eq!(
+            diagnostic.render(&Source::new("src/main.rs", text)),
+            "\
+error: unknown value
+ --> src/main.rs:5:5
+  |
+2 |     foo(
+  |        - in this call
+...
+5 |     bar)
+  |     ^^^ not found in this scope
+  |
+  = note: a note
+  = help: some help
+"
+        );
+    }
+
+    #[test]
+    fn render_wide_characters() {
+        let text = "x = \"日本\t🦀\" + y";
+        let mut ph = ParseHelper::new_char_oriented(text);
+        ph.accept_until_char('+');
+
+        let ((), plus) = ph.span_of(|ph| {
+            ph.accept_char('+');
+        });
+
+        let diagnostic = Diagnostic::error("can't add").with_label(Label::primary(plus, "here"));
+
+        assert_eq!(
+            diagnostic.render(&ph.source("input")),
+            "\
+error: can't add
+ --> input:1:12
+  |
+1 | x = \"日本    🦀\" + y
+  |                  ^ here
+"
+        );
+    }
+
+    #[test]
+    fn render_ansi() {
+        let text = "abc";
+        let ph = ParseHelper::new_char_oriented(text);
+
+        let rendered = Diagnostic::warning("oops")
+            .with_label(Label::primary(ph.mark(), ""))
+            .render_ansi(&ph.source("x"));
+
+        assert!(rendered.starts_with("\x1b[1;33mwarning\x1b[0m\x1b[1m: oops\x1b[0m\n"));
+        assert!(rendered.contains("\x1b[1;33m^\x1b[0m"));
+    }
+
+    #[test]
+    fn render_without_labels() {
+        let diagnostic = Diagnostic::<Byte>::error("empty file").with_note("files can't be empty");
+
+        assert_eq!(
+            diagnostic.render(&Source::new("x", "")),
+            "error: empty file\n = note: files can't be empty\n"
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use core::cell::Cell;
+
+use crate::{Mark, ParseHelper};
+
+// how many different expectations are remembered at the furthest position.
+const MAX_EXPECTED: usize = 8;
+
+/// Remembers the furthest position a parse helper ever reached, even after backtracking,
+/// together with what was expected there.
+///
+/// That position is almost always the right place to report an error: alternatives that failed
+/// early don't hide the one that got furthest. The tracker isn't part of a parse helper, it's
+/// told where parsing failed with [`expected_at`](Self::expected_at), and where it backtracked
+/// with [`restore_backup`](Self::restore_backup) or [`reached`](Self::reached).
+/// That keeps it cheap enough to always leave on.
+///
+/// ```
+/// use parse_helper::{FurthestFailure, ParseHelper};
+///
+/// let furthest = FurthestFailure::new();
+/// let mut ph = ParseHelper::new_char_oriented("let x = ;");
+///
+/// let statement = ph.slice_accepted_option(|ph| {
+///     ph.accept("let ")?;
+///     ph.accept_until_whitespace();
+///     ph.accept(" = ")?;
+///     ph.accept_char_with(|c| c.is_ascii_digit())
+///         .or_else(|| furthest.expected_at(ph.mark(), "a number"))?;
+///     ph.accept_char(';').map(|_| ())
+/// });
+/// assert_eq!(statement, None);
+/// assert_eq!(ph.leftover(), "let x = ;");
+///
+/// assert_eq!(furthest.byte_position(), Some(8));
+/// assert!(furthest.expected().eq(["a number"]));
+/// ```
+#[derive(Debug, Default)]
+pub struct FurthestFailure {
+    position: Cell<Option<usize>>,
+    expected: Cell<[&'static str; MAX_EXPECTED]>,
+    num_expected: Cell<usize>,
+}
+
+impl FurthestFailure {
+    /// Creates a tracker that hasn't seen any failures yet.
+    pub const fn new() -> Self {
+        Self {
+            position: Cell::new(None),
+            expected: Cell::new([""; MAX_EXPECTED]),
+            num_expected: Cell::new(0),
+        }
+    }
+
+    /// The furthest position that was reached, or `None` when nothing failed yet.
+    pub fn byte_position(&self) -> Option<usize> {
+        self.position.get()
+    }
+
+    /// The furthest position that was reached as a mark, or `None` when nothing failed yet.
+    ///
+    /// Only meaningful for the input of the parse helper the positions were recorded for.
+    pub fn mark<B>(&self) -> Option<Mark<B>> {
+        self.position.get().map(Mark::new)
+    }
+
+    /// Everything that was [`expected`](ParseHelper::expected) at the furthest position,
+    /// in the order it was first recorded.
+    ///
+    /// At most 8 different expectations are remembered, any others are dropped.
+    pub fn expected(&self) -> impl Iterator<Item = &'static str> {
+        self.expected
+            .get()
+            .into_iter()
+            .take(self.num_expected.get())
+    }
+
+    /// Forget everything that was recorded, for example to reuse the tracker for another input.
+    pub fn reset(&self) {
+        self.position.set(None);
+        self.num_expected.set(0);
+    }
+
+    /// Records that `what` was expected at `mark`.
+    ///
+    /// Always returns `None`, so it can be used as the failure case of a parser.
+    pub fn expected_at<R, B>(&self, mark: Mark<B>, what: &'static str) -> Option<R> {
+        self.record(mark.byte_position(), Some(what));
+        None
+    }
+
+    /// Records that parsing got as far as `mark`, without anything that was expected there.
+    ///
+    /// For backtracking without a backup, like when dropping a clone of the parse helper.
+    pub fn reached<B>(&self, mark: Mark<B>) {
+        self.record(mark.byte_position(), None);
+    }
+
+    /// Like [`ParseHelper::restore_backup`], but records the position that is backtracked from
+    /// first.
+    pub fn restore_backup<'a, T: ?Sized, B>(
+        &self,
+        ph: &mut ParseHelper<'a, T, B>,
+        backup: ParseHelper<'a, T, B>,
+    ) {
+        self.reached(ph.mark());
+        ph.restore_backup(backup);
+    }
+
+    pub(crate) fn record(&self, position: usize, expected: Option<&'static str>) {
+        match self.position.get() {
+            Some(furthest) if position < furthest => return,
+            Some(furthest) if position == furthest => {}
+            _ => {
+                self.position.set(Some(position));
+                self.num_expected.set(0);
+            }
+        }
+
+        let Some(expected) = expected else {
+            return;
+        };
+
+        let mut all = self.expected.get();
+        let num = self.num_expected.get();
+        if all.iter().take(num).any(|&e| e == expected) {
+            return;
+        }
+        if let Some(slot) = all.get_mut(num) {
+            *slot = expected;
+            self.expected.set(all);
+            self.num_expected.set(num.saturating_add(1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Char, FurthestFailure, Mark, ParseHelper};
+
+    fn keyword<'a>(
+        furthest: &FurthestFailure,
+        ph: &mut ParseHelper<'a, str, Char>,
+        kw: &'static str,
+    ) -> Option<&'a str> {
+        ph.accept(kw)
+            .or_else(|| furthest.expected_at(ph.mark(), kw))
+    }
+
+    #[test]
+    fn furthest_of_alternatives() {
+        let furthest = FurthestFailure::new();
+        let mut ph = ParseHelper::new_char_oriented("if x then y els z");
+
+        let parsed = ph.slice_accepted_option(|ph| {
+            keyword(&furthest, ph, "if ")?;
+            ph.accept_until_whitespace();
+            keyword(&furthest, ph, " then ")?;
+            ph.accept_until_whitespace();
+            let backup = ph.create_backup();
+            if keyword(&furthest, ph, " else ").is_none()
+                && keyword(&furthest, ph, " elif ").is_none()
+            {
+                furthest.restore_backup(ph, backup);
+                return keyword(&furthest, ph, ";").map(|_| ());
+            }
+            Some(())
+        });
+
+        assert_eq!(parsed, None);
+        assert_eq!(ph.bytes_accepted(), 0);
+        assert_eq!(furthest.mark::<Char>(), Some(Mark::new(11)));
+        assert!(furthest.expected().eq([" else ", " elif ", ";"]));
+
+        furthest.reset();
+        assert_eq!(furthest.byte_position(), None);
+        assert_eq!(furthest.expected().count(), 0);
+    }
+
+    #[test]
+    fn backtracking_with_clones() {
+        let furthest = FurthestFailure::new();
+        let ph = ParseHelper::new_char_oriented("abcd");
+
+        // what a clone expected is recorded
+        {
+            let mut attempt = ph.clone();
+            attempt.accept("ab");
+            keyword(&furthest, &mut attempt, "x");
+        }
+        assert_eq!(furthest.byte_position(), Some(2));
+        assert!(furthest.expected().eq(["x"]));
+
+        // and how far it got, when it's told before the clone is dropped
+        {
+            let mut attempt = ph.clone();
+            attempt.accept("abc");
+            furthest.reached(attempt.mark());
+        }
+        assert_eq!(furthest.byte_position(), Some(3));
+        assert_eq!(furthest.expected().count(), 0);
+        assert_eq!(ph.bytes_accepted(), 0);
+    }
+
+    #[test]
+    fn earlier_failures_are_ignored() {
+        let furthest = FurthestFailure::new();
+        furthest.record(5, Some("a"));
+        furthest.record(3, Some("b"));
+        furthest.record(5, Some("a"));
+        assert!(furthest.expected().eq(["a"]));
+
+        for i in 0..20 {
+            furthest.record(
+                5,
+                Some(["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"][i % 10]),
+            );
+        }
+        assert_eq!(furthest.expected().count(), 8);
+
+        furthest.record(6, None);
+        assert_eq!(furthest.byte_position(), Some(6));
+        assert_eq!(furthest.expected().count(), 0);
+    }
+}
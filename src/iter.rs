@@ -0,0 +1,287 @@
+use core::iter::FusedIterator;
+
+use crate::{Char, Mark, ParseHelper};
+
+impl<'a, T: ?Sized, B> ParseHelper<'a, T, B> {
+    /// Returns an iterator that parses with the closure until it returns `None`.
+    ///
+    /// Anything accepted by a successful parse stays accepted, also when the iterator is
+    /// dropped early. When the closure fails, or succeeds without accepting anything,
+    /// the parse helper is reset to where it was before that attempt and the iterator ends.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("1 22 333 x");
+    /// let numbers: Vec<u32> = ph
+    ///     .iter_with(|ph| {
+    ///         ph.accept_zero_or_more_whitespace();
+    ///         ph.accept_until_whitespace().parse().ok()
+    ///     })
+    ///     .collect();
+    ///
+    /// assert_eq!(numbers, [1, 22, 333]);
+    /// assert_eq!(ph.leftover(), " x");
+    /// ```
+    pub fn iter_with<X, F>(&mut self, f: F) -> IterWith<'_, 'a, T, B, F>
+    where
+        F: FnMut(&mut Self) -> Option<X>,
+    {
+        IterWith {
+            ph: self,
+            f,
+            finished: false,
+        }
+    }
+}
+
+impl<'a, T: ?Sized> ParseHelper<'a, T, Char>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    /// Returns an iterator over the rest of the input, split on a character.
+    ///
+    /// Like [`str::split`], the pieces never contain the character, and there is always at
+    /// least one piece, which may be empty. Every piece is accepted as it is yielded,
+    /// together with the character after it.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("a,b,,c");
+    /// let mut pieces = ph.split_on_char(',');
+    ///
+    /// assert_eq!(pieces.next(), Some("a"));
+    /// assert_eq!(pieces.next(), Some("b"));
+    /// drop(pieces);
+    /// assert_eq!(ph.leftover(), ",c");
+    ///
+    /// assert_eq!(ph.split_on_char(',').collect::<Vec<_>>(), ["", "c"]);
+    /// assert!(ph.done());
+    /// ```
+    pub fn split_on_char(&mut self, c: char) -> SplitOnChar<'_, 'a, T> {
+        SplitOnChar {
+            ph: self,
+            c,
+            finished: false,
+        }
+    }
+
+    /// Returns an iterator over the rest of the input, split on a string.
+    ///
+    /// Works like [`split_on_char`](Self::split_on_char). An empty string never matches,
+    /// so then the whole rest of the input is a single piece.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("key => value => more");
+    ///
+    /// assert_eq!(
+    ///     ph.split_on(" => ").collect::<Vec<_>>(),
+    ///     ["key", "value", "more"]
+    /// );
+    /// ```
+    pub fn split_on<P: AsRef<str>>(&mut self, pattern: P) -> SplitOn<'_, 'a, T, P> {
+        SplitOn {
+            ph: self,
+            pattern,
+            finished: false,
+        }
+    }
+
+    /// Returns an iterator over the rest of the characters in the input, together with the
+    /// mark right before each one. Every character is accepted as it is yielded.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("aé!");
+    /// let positions: Vec<_> = ph
+    ///     .chars_indexed()
+    ///     .map(|(mark, c)| (mark.byte_position(), c))
+    ///     .collect();
+    ///
+    /// assert_eq!(positions, [(0, 'a'), (1, 'é'), (3, '!')]);
+    /// ```
+    pub fn chars_indexed(&mut self) -> CharsIndexed<'_, 'a, T> {
+        CharsIndexed { ph: self }
+    }
+}
+
+/// Iterator returned by [`ParseHelper::iter_with`].
+pub struct IterWith<'p, 'a, T: ?Sized, B, F> {
+    ph: &'p mut ParseHelper<'a, T, B>,
+    f: F,
+    finished: bool,
+}
+
+impl<'a, T: ?Sized, B, X, F> Iterator for IterWith<'_, 'a, T, B, F>
+where
+    F: FnMut(&mut ParseHelper<'a, T, B>) -> Option<X>,
+{
+    type Item = X;
+
+    fn next(&mut self) -> Option<X> {
+        if self.finished {
+            return None;
+        }
+
+        let backup = self.ph.create_backup();
+        match (self.f)(self.ph) {
+            Some(item) if self.ph.byte_position != backup.byte_position => Some(item),
+            _ => {
+                self.ph.restore_backup(backup);
+                self.finished = true;
+                None
+            }
+        }
+    }
+}
+
+impl<'a, T: ?Sized, B, X, F> FusedIterator for IterWith<'_, 'a, T, B, F> where
+    F: FnMut(&mut ParseHelper<'a, T, B>) -> Option<X>
+{
+}
+
+/// Iterator returned by [`ParseHelper::split_on_char`].
+pub struct SplitOnChar<'p, 'a, T: ?Sized> {
+    ph: &'p mut ParseHelper<'a, T, Char>,
+    c: char,
+    finished: bool,
+}
+
+impl<'a, T: ?Sized> Iterator for SplitOnChar<'_, 'a, T>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.finished {
+            return None;
+        }
+
+        let piece = self.ph.accept_until_char(self.c);
+        self.finished = self.ph.accept_char(self.c).is_none();
+        Some(piece)
+    }
+}
+
+impl<T: ?Sized> FusedIterator for SplitOnChar<'_, '_, T> where T: AsRef<str> + AsRef<[u8]> {}
+
+/// Iterator returned by [`ParseHelper::split_on`].
+pub struct SplitOn<'p, 'a, T: ?Sized, P> {
+    ph: &'p mut ParseHelper<'a, T, Char>,
+    pattern: P,
+    finished: bool,
+}
+
+impl<'a, T: ?Sized, P: AsRef<str>> Iterator for SplitOn<'_, 'a, T, P>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.finished {
+            return None;
+        }
+
+        let pattern = self.pattern.as_ref();
+        let leftover = self.ph.leftover();
+        let found = Some(pattern)
+            .filter(|p| !p.is_empty())
+            .and_then(|p| leftover.find(p));
+
+        let (piece, skip) = match found {
+            Some(idx) => (leftover.get(..idx).unwrap_or_default(), pattern.len()),
+            None => {
+                self.finished = true;
+                (leftover, 0)
+            }
+        };
+
+        // both the piece and the pattern end on utf8 boundaries
+        self.ph.advance(piece.len().saturating_add(skip));
+        Some(piece)
+    }
+}
+
+impl<T: ?Sized, P: AsRef<str>> FusedIterator for SplitOn<'_, '_, T, P> where
+    T: AsRef<str> + AsRef<[u8]>
+{
+}
+
+/// Iterator returned by [`ParseHelper::chars_indexed`].
+pub struct CharsIndexed<'p, 'a, T: ?Sized> {
+    ph: &'p mut ParseHelper<'a, T, Char>,
+}
+
+impl<T: ?Sized> Iterator for CharsIndexed<'_, '_, T>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    type Item = (Mark<Char>, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mark = self.ph.mark();
+        let c = self.ph.upcoming_char()?;
+        self.ph.accept_char(c);
+        Some((mark, c))
+    }
+}
+
+impl<T: ?Sized> FusedIterator for CharsIndexed<'_, '_, T> where T: AsRef<str> + AsRef<[u8]> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::ParseHelper;
+
+    #[test]
+    fn iter_with_stops_without_progress() {
+        let mut ph = ParseHelper::new_char_oriented("aab");
+        let mut calls = 0;
+        let accepted: Vec<_> = ph
+            .iter_with(|ph| {
+                calls += 1;
+                Some(ph.accept_until_char('b'))
+            })
+            .collect();
+
+        assert_eq!(accepted, ["aa"]);
+        assert_eq!(calls, 2);
+        assert_eq!(ph.leftover(), "b");
+    }
+
+    #[test]
+    fn split_like_std() {
+        for input in ["", ",", "a", "a,", ",b,,c", "é,,🦀"] {
+            let mut ph = ParseHelper::new_char_oriented(input);
+            let expected: Vec<_> = input.split(',').collect();
+            assert_eq!(ph.split_on_char(',').collect::<Vec<_>>(), expected);
+            assert!(ph.done());
+
+            let mut ph = ParseHelper::new_char_oriented(input);
+            let expected: Vec<_> = input.split(",,").collect();
+            assert_eq!(ph.split_on(",,").collect::<Vec<_>>(), expected);
+            assert!(ph.done());
+
+            let mut ph = ParseHelper::new_char_oriented(input);
+            assert_eq!(ph.split_on("").collect::<Vec<_>>(), [input]);
+        }
+    }
+
+    #[test]
+    fn chars_indexed() {
+        let input = "h€llo";
+        let mut ph = ParseHelper::new_char_oriented(input);
+
+        let chars: Vec<_> = ph
+            .chars_indexed()
+            .map(|(mark, c)| (mark.byte_position(), c))
+            .collect();
+        assert_eq!(chars, input.char_indices().collect::<Vec<_>>());
+        assert!(ph.done());
+    }
+}
@@ -0,0 +1,795 @@
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::{
+    fmt::{self, Display, Formatter},
+    iter::FusedIterator,
+    str::Chars,
+};
+
+use crate::{Char, ParseHelper, SyntaxError};
+
+/// How deep arrays and objects can be nested, also when the
+/// [maximum depth](ParseHelper::set_max_depth) of the parse helper is higher.
+const MAX_NESTING: usize = 256;
+
+/// Error returned when parsing JSON fails.
+pub type JsonError = SyntaxError;
+
+/// A string in JSON input, as it's written between the quotes.
+///
+/// Its escapes are checked while parsing, but only decoded when asked for, so reading
+/// JSON doesn't have to allocate.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct JsonStr<'a> {
+    raw: &'a str,
+}
+
+impl<'a> JsonStr<'a> {
+    /// The string as written in the input, with escapes but without the quotes.
+    pub fn raw(&self) -> &'a str {
+        self.raw
+    }
+
+    /// Returns `true` if the string contains escapes, so [`unescape`](Self::unescape)
+    /// has to allocate.
+    pub fn has_escapes(&self) -> bool {
+        self.raw.contains('\\')
+    }
+
+    /// Iterates over the characters of the string, with escapes decoded.
+    pub fn chars(&self) -> JsonChars<'a> {
+        JsonChars {
+            rest: self.raw.chars(),
+        }
+    }
+
+    /// Returns the string with escapes decoded, which is only owned if it has escapes.
+    #[cfg(feature = "alloc")]
+    pub fn unescape(&self) -> Cow<'a, str> {
+        if self.has_escapes() {
+            Cow::Owned(self.chars().collect())
+        } else {
+            Cow::Borrowed(self.raw)
+        }
+    }
+}
+
+/// Iterator returned by [`JsonStr::chars`].
+#[derive(Clone, Debug)]
+pub struct JsonChars<'a> {
+    rest: Chars<'a>,
+}
+
+impl Iterator for JsonChars<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        match self.rest.next()? {
+            '\\' => decode_escape(&mut self.rest),
+            c => Some(c),
+        }
+    }
+}
+
+impl FusedIterator for JsonChars<'_> {}
+
+// decodes the part of an escape after the backslash
+fn decode_escape(chars: &mut Chars<'_>) -> Option<char> {
+    Some(match chars.next()? {
+        c @ ('"' | '\\' | '/') => c,
+        'b' => '\u{8}',
+        'f' => '\u{c}',
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        'u' => {
+            let high = decode_hex4(chars)?;
+            if !(0xD800..0xE000).contains(&high) {
+                return char::from_u32(high);
+            }
+
+            // a surrogate pair, written as two escapes
+            if high >= 0xDC00 || chars.next()? != '\\' || chars.next()? != 'u' {
+                return None;
+            }
+            let low = decode_hex4(chars)?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return None;
+            }
+            let pair = [u16::try_from(high).ok()?, u16::try_from(low).ok()?];
+            char::decode_utf16(pair).next()?.ok()?
+        }
+        _ => return None,
+    })
+}
+
+fn decode_hex4(chars: &mut Chars<'_>) -> Option<u32> {
+    (0..4).try_fold(0, |res, _| Some(res << 4 | chars.next()?.to_digit(16)?))
+}
+
+/// A number in JSON input, as it's written.
+///
+/// JSON numbers have no limits on their size or precision,
+/// so it's up to the user how to interpret them.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct JsonNumber<'a> {
+    raw: &'a str,
+}
+
+impl<'a> JsonNumber<'a> {
+    /// The number as written in the input.
+    pub fn raw(&self) -> &'a str {
+        self.raw
+    }
+
+    /// Returns the number if it's written as an integer that fits in a `u64`.
+    pub fn as_u64(&self) -> Option<u64> {
+        self.raw.parse().ok()
+    }
+
+    /// Returns the number if it's written as an integer that fits in an `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.raw.parse().ok()
+    }
+
+    /// Returns the number as the closest `f64`.
+    pub fn as_f64(&self) -> Option<f64> {
+        self.raw.parse().ok()
+    }
+}
+
+impl Display for JsonNumber<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.raw)
+    }
+}
+
+/// An event from [`ParseHelper::json_events`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum JsonEvent<'a> {
+    /// `{`
+    StartObject,
+    /// `}`
+    EndObject,
+    /// `[`
+    StartArray,
+    /// `]`
+    EndArray,
+    /// The name of a member of an object, always followed by its value.
+    Key(JsonStr<'a>),
+    /// A string value.
+    String(JsonStr<'a>),
+    /// A number value.
+    Number(JsonNumber<'a>),
+    /// `true` or `false`
+    Bool(bool),
+    /// `null`
+    Null,
+}
+
+// which arrays and objects we're in, as one bit per level so it doesn't have to allocate
+#[derive(Clone, Copy, Default)]
+struct Nesting {
+    // set bits are objects, the others arrays
+    objects: [u128; 2],
+    depth: usize,
+}
+
+impl Nesting {
+    fn bit(level: usize) -> (usize, u128) {
+        (level / 128, 1 << (level % 128))
+    }
+
+    fn push(&mut self, object: bool) {
+        let (idx, bit) = Self::bit(self.depth);
+        if let Some(word) = self.objects.get_mut(idx) {
+            if object {
+                *word |= bit;
+            } else {
+                *word &= !bit;
+            }
+        }
+        self.depth = self.depth.saturating_add(1);
+    }
+
+    fn pop(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    fn in_object(&self) -> bool {
+        let Some(level) = self.depth.checked_sub(1) else {
+            return false;
+        };
+        let (idx, bit) = Self::bit(level);
+        self.objects.get(idx).is_some_and(|word| word & bit != 0)
+    }
+}
+
+// what the parser expects next
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Value,
+    FirstKey,
+    FirstElement,
+    AfterValue,
+    Finished,
+}
+
+/// A pull parser for JSON, created by [`ParseHelper::json_events`].
+///
+/// Yields events for a single value, and the whitespace around it. After an error,
+/// the parse helper stays where the error was, and no more events are yielded.
+pub struct JsonParser<'p, 'a, T: ?Sized> {
+    ph: &'p mut ParseHelper<'a, T, Char>,
+    nesting: Nesting,
+    state: State,
+}
+
+impl<'a, T: ?Sized> JsonParser<'_, 'a, T>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    // an error at the current position
+    fn error(&self, expected: &'static str) -> JsonError {
+        let message = match self.ph.upcoming_char() {
+            Some(_) => expected,
+            None => "unexpected end of input",
+        };
+        self.ph.syntax_error(self.ph.byte_position, message)
+    }
+
+    fn whitespace(&mut self) {
+        self.ph
+            .accept_until_char_with(|c| !matches!(c, ' ' | '\t' | '\n' | '\r'));
+    }
+
+    fn step(&mut self) -> Result<JsonEvent<'a>, JsonError> {
+        self.whitespace();
+
+        match self.state {
+            State::Value => self.value(),
+            State::FirstElement if self.ph.accept_char(']').is_some() => {
+                Ok(self.end(JsonEvent::EndArray))
+            }
+            State::FirstElement => self.value(),
+            State::FirstKey if self.ph.accept_char('}').is_some() => {
+                Ok(self.end(JsonEvent::EndObject))
+            }
+            State::FirstKey => self.key(),
+            State::AfterValue => {
+                let in_object = self.nesting.in_object();
+                let (close, end, expected) = match in_object {
+                    true => ('}', JsonEvent::EndObject, "expected `,` or `}`"),
+                    false => (']', JsonEvent::EndArray, "expected `,` or `]`"),
+                };
+
+                if self.ph.accept_char(close).is_some() {
+                    Ok(self.end(end))
+                } else if self.ph.accept_char(',').is_some() {
+                    self.whitespace();
+                    match in_object {
+                        true => self.key(),
+                        false => self.value(),
+                    }
+                } else {
+                    Err(self.error(expected))
+                }
+            }
+            State::Finished => Err(self.error("expected the end of the input")),
+        }
+    }
+
+    fn key(&mut self) -> Result<JsonEvent<'a>, JsonError> {
+        if self.ph.upcoming_char() != Some('"') {
+            return Err(self.error("expected a string"));
+        }
+        let key = self.ph.json_string()?;
+
+        self.whitespace();
+        if self.ph.accept_char(':').is_none() {
+            return Err(self.error("expected `:`"));
+        }
+        self.state = State::Value;
+
+        Ok(JsonEvent::Key(key))
+    }
+
+    fn value(&mut self) -> Result<JsonEvent<'a>, JsonError> {
+        let event = match self.ph.upcoming_char() {
+            Some(c @ ('{' | '[')) => {
+                if self.nesting.depth >= MAX_NESTING.min(self.ph.max_depth()) {
+                    return Err(self.error("nested too deeply"));
+                }
+                self.ph.accept_char(c);
+
+                let object = c == '{';
+                self.nesting.push(object);
+                return Ok(match object {
+                    true => {
+                        self.state = State::FirstKey;
+                        JsonEvent::StartObject
+                    }
+                    false => {
+                        self.state = State::FirstElement;
+                        JsonEvent::StartArray
+                    }
+                });
+            }
+            Some('"') => JsonEvent::String(self.ph.json_string()?),
+            Some('-' | '0'..='9') => JsonEvent::Number(self.ph.json_number()?),
+            _ if self.ph.accept("true").is_some() => JsonEvent::Bool(true),
+            _ if self.ph.accept("false").is_some() => JsonEvent::Bool(false),
+            _ if self.ph.accept("null").is_some() => JsonEvent::Null,
+            _ => return Err(self.error("expected a value")),
+        };
+
+        self.after_value();
+        Ok(event)
+    }
+
+    fn end(&mut self, event: JsonEvent<'a>) -> JsonEvent<'a> {
+        self.nesting.pop();
+        self.after_value();
+        event
+    }
+
+    fn after_value(&mut self) {
+        if self.nesting.depth == 0 {
+            self.whitespace();
+            self.state = State::Finished;
+        } else {
+            self.state = State::AfterValue;
+        }
+    }
+}
+
+impl<'a, T: ?Sized> Iterator for JsonParser<'_, 'a, T>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    type Item = Result<JsonEvent<'a>, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.state == State::Finished {
+            return None;
+        }
+
+        let res = self.step();
+        if res.is_err() {
+            self.state = State::Finished;
+        }
+        Some(res)
+    }
+}
+
+impl<T: ?Sized> FusedIterator for JsonParser<'_, '_, T> where T: AsRef<str> + AsRef<[u8]> {}
+
+/// A JSON value, borrowing from the input where it can.
+#[cfg(feature = "alloc")]
+#[derive(Clone, PartialEq, Debug)]
+pub enum JsonValue<'a> {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// A number, as it's written
+    Number(JsonNumber<'a>),
+    /// A string, which is only owned if it had escapes
+    String(Cow<'a, str>),
+    /// An array
+    Array(Vec<JsonValue<'a>>),
+    /// An object, with its members in the order they were written, including duplicates
+    Object(Vec<(Cow<'a, str>, JsonValue<'a>)>),
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> JsonValue<'a> {
+    /// Returns the value of the first member named `key`, if this is an object.
+    pub fn get(&self, key: &str) -> Option<&JsonValue<'a>> {
+        self.as_object()?
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// Returns `true` if this is `null`.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Returns the boolean, if this is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Returns the number, if this is one.
+    pub fn as_number(&self) -> Option<JsonNumber<'a>> {
+        match self {
+            Self::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Returns the string, if this is one.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements, if this is an array.
+    pub fn as_array(&self) -> Option<&[JsonValue<'a>]> {
+        match self {
+            Self::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Returns the members, if this is an object.
+    pub fn as_object(&self) -> Option<&[(Cow<'a, str>, JsonValue<'a>)]> {
+        match self {
+            Self::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+}
+
+// an array or object that is still being built
+#[cfg(feature = "alloc")]
+enum Frame<'a> {
+    Array(Vec<JsonValue<'a>>),
+    Object(Vec<(Cow<'a, str>, JsonValue<'a>)>, Option<Cow<'a, str>>),
+}
+
+impl<'a, T: ?Sized> ParseHelper<'a, T, Char>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    /// Returns a pull parser that yields the events of a single JSON value.
+    ///
+    /// It doesn't allocate, and keeps strings and numbers as they're written in the input.
+    ///
+    /// ```
+    /// use parse_helper::{JsonEvent, ParseHelper};
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented(r#"{"a": [1, "\n"]} rest"#);
+    /// let events: Vec<_> = ph.json_events().collect::<Result<_, _>>().unwrap();
+    ///
+    /// assert!(matches!(
+    ///     events.as_slice(),
+    ///     [
+    ///         JsonEvent::StartObject,
+    ///         JsonEvent::Key(a),
+    ///         JsonEvent::StartArray,
+    ///         JsonEvent::Number(n),
+    ///         JsonEvent::String(s),
+    ///         JsonEvent::EndArray,
+    ///         JsonEvent::EndObject,
+    ///     ] if a.raw() == "a" && n.as_u64() == Some(1) && s.chars().eq(['\n'])
+    /// ));
+    /// assert_eq!(ph.leftover(), "rest");
+    /// ```
+    pub fn json_events(&mut self) -> JsonParser<'_, 'a, T> {
+        JsonParser {
+            ph: self,
+            nesting: Nesting::default(),
+            state: State::Value,
+        }
+    }
+
+    /// Checks that the rest of the input is a single JSON value, with optional whitespace
+    /// around it, without allocating.
+    ///
+    /// Returns an error without accepting anything when it isn't.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    ///
+    /// assert!(ParseHelper::new_char_oriented(" [1, {}] ").validate_json().is_ok());
+    ///
+    /// let err = ParseHelper::new_char_oriented("[1, 2,]").validate_json().unwrap_err();
+    /// assert_eq!(err.message(), "expected a value");
+    /// assert_eq!(err.mark().byte_position(), 6);
+    /// ```
+    pub fn validate_json(&mut self) -> Result<(), JsonError> {
+        let old = self.create_backup();
+
+        let res = self
+            .json_events()
+            .try_for_each(|event| event.map(drop))
+            .and_then(|()| self.expect_end_of_json());
+        if res.is_err() {
+            self.restore_backup(old);
+        }
+
+        res
+    }
+
+    /// Parses the rest of the input as a single JSON value, with optional whitespace
+    /// around it.
+    ///
+    /// Returns an error without accepting anything when it isn't valid JSON.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented(r#"{"name": "pi", "value": 3.14}"#);
+    /// let value = ph.parse_json().unwrap();
+    ///
+    /// assert_eq!(value.get("name").and_then(|v| v.as_str()), Some("pi"));
+    /// assert_eq!(value.get("value").and_then(|v| v.as_number()).unwrap().raw(), "3.14");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn parse_json(&mut self) -> Result<JsonValue<'a>, JsonError> {
+        let old = self.create_backup();
+
+        let res = self.build_json().and_then(|value| {
+            self.expect_end_of_json()?;
+            Ok(value)
+        });
+        if res.is_err() {
+            self.restore_backup(old);
+        }
+
+        res
+    }
+
+    fn expect_end_of_json(&self) -> Result<(), JsonError> {
+        match self.done() {
+            true => Ok(()),
+            false => Err(self.syntax_error(self.byte_position, "expected the end of the input")),
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn build_json(&mut self) -> Result<JsonValue<'a>, JsonError> {
+        let mut stack = Vec::new();
+        let mut root = None;
+
+        for event in self.json_events() {
+            let value = match event? {
+                JsonEvent::StartObject => {
+                    stack.push(Frame::Object(Vec::new(), None));
+                    continue;
+                }
+                JsonEvent::StartArray => {
+                    stack.push(Frame::Array(Vec::new()));
+                    continue;
+                }
+                JsonEvent::Key(key) => {
+                    if let Some(Frame::Object(_, pending)) = stack.last_mut() {
+                        *pending = Some(key.unescape());
+                    }
+                    continue;
+                }
+                JsonEvent::EndObject | JsonEvent::EndArray => match stack.pop() {
+                    Some(Frame::Object(members, _)) => JsonValue::Object(members),
+                    Some(Frame::Array(elements)) => JsonValue::Array(elements),
+                    None => continue,
+                },
+                JsonEvent::String(s) => JsonValue::String(s.unescape()),
+                JsonEvent::Number(n) => JsonValue::Number(n),
+                JsonEvent::Bool(b) => JsonValue::Bool(b),
+                JsonEvent::Null => JsonValue::Null,
+            };
+
+            match stack.last_mut() {
+                Some(Frame::Array(elements)) => elements.push(value),
+                Some(Frame::Object(members, pending)) => {
+                    let key = pending.take().unwrap_or_else(|| Cow::Owned(String::new()));
+                    members.push((key, value));
+                }
+                None => root = Some(value),
+            }
+        }
+
+        root.ok_or_else(|| self.syntax_error(self.byte_position, "expected a value"))
+    }
+}
+
+// the tokens of JSON that aren't a single character,
+// shared by the pull parser and the serde deserializer
+impl<'a, T: ?Sized> ParseHelper<'a, T, Char>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    // a string, starting at its opening quote
+    pub(crate) fn json_string(&mut self) -> Result<JsonStr<'a>, JsonError> {
+        let start = self.byte_position;
+        self.accept_char('"');
+
+        let rest = self.leftover();
+        let mut chars = rest.chars();
+        loop {
+            let offset = rest.len().saturating_sub(chars.as_str().len());
+            let position = self.byte_position.saturating_add(offset);
+
+            match chars.next() {
+                Some('"') => {
+                    self.advance(offset.saturating_add(1));
+                    let raw = rest.get(..offset).unwrap_or_default();
+                    return Ok(JsonStr { raw });
+                }
+                Some('\\') => {
+                    if decode_escape(&mut chars).is_none() {
+                        return Err(self.syntax_error(position, "invalid escape"));
+                    }
+                }
+                Some(c) if c < ' ' => {
+                    return Err(self.syntax_error(position, "control character in string"));
+                }
+                Some(_) => {}
+                None => return Err(self.syntax_error(start, "unterminated string")),
+            }
+        }
+    }
+
+    // a number, starting at its first character
+    pub(crate) fn json_number(&mut self) -> Result<JsonNumber<'a>, JsonError> {
+        let rest = self.leftover();
+        let bytes = rest.as_bytes();
+        let byte = |i: usize| bytes.get(i).copied();
+        let digits = |i: usize| {
+            bytes
+                .get(i..)
+                .map_or(0, |b| b.iter().take_while(|b| b.is_ascii_digit()).count())
+        };
+
+        let mut len = usize::from(byte(0) == Some(b'-'));
+        let mut valid = match byte(len) {
+            Some(b'0') => {
+                len = len.saturating_add(1);
+                true
+            }
+            Some(b'1'..=b'9') => {
+                len = len.saturating_add(digits(len));
+                true
+            }
+            _ => false,
+        };
+        if byte(len) == Some(b'.') {
+            let n = digits(len.saturating_add(1));
+            valid &= n > 0;
+            len = len.saturating_add(1).saturating_add(n);
+        }
+        if matches!(byte(len), Some(b'e' | b'E')) {
+            len = len.saturating_add(1);
+            len = len.saturating_add(usize::from(matches!(byte(len), Some(b'+' | b'-'))));
+            let n = digits(len);
+            valid &= n > 0;
+            len = len.saturating_add(n);
+        }
+
+        match rest.get(..len) {
+            Some(raw) if valid => {
+                self.advance(len);
+                Ok(JsonNumber { raw })
+            }
+            _ => Err(self.syntax_error(self.byte_position, "invalid number")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonEvent, ParseHelper};
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn strings() {
+        use crate::JsonValue;
+        use alloc::borrow::Cow;
+
+        let mut ph = ParseHelper::new_char_oriented(r#"["plain", "a\"\\\/\b\f\n\r\t", "é🦀"]"#);
+        let value = ph.parse_json().unwrap();
+        let strings = value.as_array().unwrap();
+
+        assert!(matches!(
+            strings[0],
+            JsonValue::String(Cow::Borrowed("plain"))
+        ));
+        assert_eq!(strings[1].as_str(), Some("a\"\\/\u{8}\u{c}\n\r\t"));
+        assert_eq!(strings[2].as_str(), Some("é🦀"));
+
+        for invalid in [
+            r#""\ud800""#,
+            r#""\udc00\ud800""#,
+            r#""\x""#,
+            r#""\u12""#,
+            "\"\t\"",
+        ] {
+            let mut ph = ParseHelper::new_char_oriented(invalid);
+            assert!(ph.validate_json().is_err(), "{invalid}");
+            assert_eq!(ph.bytes_accepted(), 0);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn numbers() {
+        for valid in [
+            "0",
+            "-0",
+            "1.5",
+            "-12e+3",
+            "1E-2",
+            "0.0e0",
+            "123456789012345678901234567890",
+        ] {
+            let mut ph = ParseHelper::new_char_oriented(valid);
+            let n = ph.parse_json().unwrap().as_number().unwrap();
+            assert_eq!(n.raw(), valid);
+            assert!(n.as_f64().is_some());
+        }
+
+        for invalid in [
+            "01", "-", "1.", ".5", "1e", "1e+", "+1", "0x10", "1.5.3", "-01",
+        ] {
+            let mut ph = ParseHelper::new_char_oriented(invalid);
+            assert!(ph.validate_json().is_err(), "{invalid}");
+        }
+
+        let n = ParseHelper::new_char_oriented("-3").parse_json().unwrap();
+        assert_eq!(n.as_number().unwrap().as_i64(), Some(-3));
+        assert_eq!(n.as_number().unwrap().as_u64(), None);
+    }
+
+    #[test]
+    fn events_stop_after_one_value() {
+        let mut ph = ParseHelper::new_char_oriented("1 2");
+        let events: Vec<_> = ph.json_events().collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!(ph.leftover(), "2");
+
+        let mut ph = ParseHelper::new_char_oriented("[1 2]");
+        let events: Vec<_> = ph.json_events().collect();
+        assert!(
+            matches!(events.as_slice(), [Ok(JsonEvent::StartArray), Ok(JsonEvent::Number(_)), Err(e)] if e.message() == "expected `,` or `]`")
+        );
+        assert_eq!(ph.leftover(), "2]");
+    }
+
+    #[test]
+    fn nesting() {
+        let deep = "[{\"a\":".repeat(100) + "0" + &"}]".repeat(100);
+        let mut ph = ParseHelper::new_char_oriented(deep.as_str());
+        assert!(ph.validate_json().is_ok());
+
+        let deeper = "[".repeat(257) + &"]".repeat(257);
+        let err = ParseHelper::new_char_oriented(deeper.as_str())
+            .validate_json()
+            .unwrap_err();
+        assert_eq!(err.message(), "nested too deeply");
+        assert_eq!(err.mark().byte_position(), 256);
+
+        let mut ph = ParseHelper::new_char_oriented("[[[]]]");
+        ph.set_max_depth(2);
+        assert!(ph.validate_json().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn objects_keep_duplicates_in_order() {
+        use crate::JsonValue;
+
+        let mut ph = ParseHelper::new_char_oriented(r#"{"b": 1, "a": {}, "b": null}"#);
+        let value = ph.parse_json().unwrap();
+        let keys: Vec<_> = value
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(k, _)| k.as_ref())
+            .collect();
+
+        assert_eq!(keys, ["b", "a", "b"]);
+        assert_eq!(
+            value.get("b").and_then(|v| v.as_number()).unwrap().raw(),
+            "1"
+        );
+        assert_eq!(value.get("a"), Some(&JsonValue::Object(Vec::new())));
+    }
+}
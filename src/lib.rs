@@ -1,15 +1,28 @@
 #![doc=include_str!("../README.md")]
-#![cfg_attr(not(feature="std"), no_std)]
+// unit tests always have std available, whatever the features
+#![cfg_attr(not(any(feature="std", test)), no_std)]
 #![deny(missing_docs)]
-
-#[cfg(feature="alloc")]
+// the parse helper is meant for untrusted input, so it should only panic where it documents it
+#![cfg_attr(not(test), deny(
+    clippy::panic,
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::unreachable,
+    clippy::todo,
+    clippy::unimplemented,
+    clippy::missing_panics_doc,
+    clippy::arithmetic_side_effects,
+))]
+
+#[cfg(any(feature="alloc", test))]
 extern crate alloc;
 
 
 #[cfg(feature="icu")]
 pub use icu_properties;
 
-use std::{marker::PhantomData, ops::Deref};
+use core::{marker::PhantomData, ops::Deref};
 
 // operations only valid on boundary::Char parse helpers
 mod string;
@@ -17,9 +30,83 @@ mod string;
 // operations only valid on boundary::Byte parse helpers
 mod byte;
 
+// operations only valid on boundary::Byte parse helpers,
+// for reading fixed width binary encoded values
+mod binary;
+
+// operations only valid on boundary::Byte parse helpers,
+// for decoding variable length integers
+mod varint;
+
+// a view on byte oriented parse helpers that reads single bits
+mod bits;
+
 // operations valid on any parse helper
 mod any;
 
+// marks that can only be used with the input they came from
+mod brand;
+
+// ranges of input between two marks
+mod span;
+
+// skipping over invalid input to continue parsing after an error
+mod recover;
+
+// remembering how far parsing got before backtracking
+mod furthest;
+
+// limiting how deep parsers recurse
+mod nested;
+
+// parsing input that is read as it goes
+#[cfg(feature="std")]
+mod stream;
+
+// parsing input that arrives in chunks
+mod partial;
+
+// parsing input that is split over multiple chunks, like a rope
+#[cfg(feature="alloc")]
+mod chunked;
+
+// caching the outcome of parsing rules, for packrat parsing
+#[cfg(feature="alloc")]
+mod memo;
+
+// recording which parsing methods were called, for debugging
+mod trace;
+
+// rendering messages about the input like rustc does
+#[cfg(feature="alloc")]
+mod diagnostic;
+
+// parse helpers that own their input instead of borrowing it
+mod owned;
+
+// sets of bytes, as predicates that can be used in const contexts
+mod byte_set;
+
+// const counterparts of byte oriented operations, for parsing at compile time
+mod const_fn;
+
+// iterators that repeat a parse, or split the input
+mod iter;
+
+// deserializing a JSON-compatible format with serde
+#[cfg(all(feature="serde", feature="alloc"))]
+mod de;
+
+// errors for malformed input in the text formats below
+mod syntax;
+
+// parsing and validating JSON, into values or as a stream of events
+mod json;
+
+// reading CSV and other delimiter separated records
+#[cfg(feature="alloc")]
+mod csv;
+
 // operations to construct a parse helper
 mod new;
 
@@ -30,7 +117,39 @@ mod boundary;
 mod common;
 
 pub use boundary::{Byte, Char};
-pub use any::Mark;
+pub use any::{Mark, OutOfBounds};
+pub use binary::{Be, Le, Ne};
+pub use brand::{Branded, BrandedMark};
+pub use span::{Span, Spanned};
+pub use furthest::FurthestFailure;
+pub use nested::NestingTooDeep;
+pub use partial::{PartialError, PartialParseHelper};
+pub use owned::{Fragment, OwnedParseHelper};
+#[cfg(feature="alloc")]
+pub use chunked::{Chunked, ChunkedInput, Rope};
+pub use string::LineColumn;
+#[cfg(feature="alloc")]
+pub use diagnostic::{Diagnostic, Label, Severity, Source};
+#[cfg(feature="alloc")]
+pub use recover::ErrorSink;
+#[cfg(feature="alloc")]
+pub use memo::Memo;
+#[cfg(feature="std")]
+pub use stream::{StreamBackup, StreamingParseHelper};
+#[cfg(all(feature="serde", feature="alloc"))]
+pub use de::{DeserializeError, Deserializer};
+#[cfg(feature="trace")]
+pub use trace::{TraceEntry, Tracer};
+pub use byte_set::ByteSet;
+pub use syntax::SyntaxError;
+pub use json::{JsonChars, JsonError, JsonEvent, JsonNumber, JsonParser, JsonStr};
+#[cfg(feature="alloc")]
+pub use json::JsonValue;
+#[cfg(feature="alloc")]
+pub use csv::{CsvDialect, CsvError, CsvEscape, CsvField, CsvRecord, CsvRecords};
+pub use iter::{CharsIndexed, IterWith, SplitOn, SplitOnChar};
+pub use bits::{BitMark, BitReader, Lsb0, Msb0};
+pub use varint::{SignedVarInt, UnsignedVarInt, VarIntError, VarIntErrorKind};
 
 /// A wrapper around a bytes-like or string-like object that allows you to extract parts of it,
 /// maybe to help implement a parser.
@@ -56,7 +175,7 @@ pub use any::Mark;
 /// * `*with` means that the function takes some closure that determines whether the operation
 ///   should succeed or fail. The closure gets to inspect the input.
 /// * `*upcoming` always refers to things that have not yet been accepted, but doesn't actually
-///    accepts that thing. It just lets you look ahead.
+///   accepts that thing. It just lets you look ahead.
 ///   `upcoming_byte` returns the next byte that would be accepted.
 /// * `*until*` performs some operations until a certain condition becomes true for the first time.
 ///   The part of the input that was accepted until the where the condition evaluates to true,
@@ -75,15 +194,36 @@ pub use any::Mark;
 /// Some methods depend on the boundary assumption; there are byte and utf8 oriented parse helpers. 
 /// A utf8 oriented parse helper can never have an offset that isn't on a utf8 boundary, while a
 /// byte oriented parse helper can have that.
+///
+/// # Panics
+///
+/// Parse helpers are meant to be fed untrusted input, so no method panics on any input,
+/// except for the few that document a `# Panics` section (like [`skip_bytes`] and [`slice`]).
+/// Those all have a `try_*` counterpart that returns an error instead.
+///
+/// [`skip_bytes`]: ParseHelper::skip_bytes
+/// [`slice`]: ParseHelper::slice
 pub struct ParseHelper<'a, T: ?Sized, B> {
     input: &'a T,
     byte_position: usize,
     boundary_assumption: PhantomData<B>,
+    depth: usize,
+    max_depth: usize,
+    #[cfg(feature="trace")]
+    tracer: Option<&'a Tracer>,
 }
 
 impl<'a, T: ?Sized, B> Clone for ParseHelper<'a, T, B> {
     fn clone(&self) -> Self {
-        Self { input: self.input, byte_position: self.byte_position, boundary_assumption: PhantomData }
+        Self {
+            input: self.input,
+            byte_position: self.byte_position,
+            boundary_assumption: PhantomData,
+            depth: self.depth,
+            max_depth: self.max_depth,
+            #[cfg(feature="trace")]
+            tracer: self.tracer,
+        }
     }
 }
 
@@ -99,7 +239,7 @@ impl<'a, T: ?Sized, B> Deref for ParseHelper<'a, T, B> {
 
 #[cfg(test)]
 mod tests {
-    use crate::ParseHelper;
+    use crate::{OutOfBounds, ParseHelper};
 
     #[test]
     fn test_bytes_left() {
@@ -149,6 +289,92 @@ mod tests {
         x.skip_byte();
     }
 
+    #[test]
+    fn test_try_skip_past_end() {
+        let mut x = ParseHelper::new_byte_oriented("hi");
+        assert_eq!(x.try_skip_bytes(3), Err(OutOfBounds));
+        assert_eq!(x.try_skip_byte(), Ok(()));
+        assert_eq!(x.try_skip_byte(), Ok(()));
+        assert_eq!(x.try_skip_byte(), Err(OutOfBounds));
+        assert_eq!(x.try_skip_bytes(usize::MAX), Err(OutOfBounds));
+        assert!(x.done());
+    }
+
+    // calls every method that shouldn't panic at, and with marks past, the end of the input.
+    #[test]
+    fn test_no_panics_at_end_of_input() {
+        for input in ["", "a", "\u{e9}", "a\u{1f980}"] {
+            let longer = format!("{input}\u{1f980}bc");
+            let mut past_end_byte = ParseHelper::new_byte_oriented(longer.as_str());
+            past_end_byte.skip_bytes(longer.len());
+            let past_end_byte = past_end_byte.mark();
+            let mut past_end_char = ParseHelper::new_char_oriented(longer.as_str());
+            past_end_char.accept_until_char(char::MAX);
+            let past_end_char = past_end_char.mark();
+
+            let mut ph = ParseHelper::new_byte_oriented(input);
+            ph.skip_bytes(input.len());
+            let start = ParseHelper::new_byte_oriented(input).mark();
+
+            for _ in 0..2 {
+                let end = ph.mark();
+                assert!(ph.done());
+                assert_eq!(ph.bytes_left(), 0);
+                assert_eq!(ph.bytes_accepted(), input.len());
+                assert_eq!(ph.upcoming_byte(), None);
+                assert_eq!(ph.leftover(), b"");
+                assert!(!ph.accept_byte(b'a'));
+                assert_eq!(ph.accept_byte_with(|_| true), None);
+                assert_eq!(ph.accept("a"), None);
+                assert_eq!(ph.accept(""), Some(b"".as_slice()));
+                assert_eq!(ph.accept_until_byte(b'a'), b"");
+                assert_eq!(ph.accept_until_byte_with(|_| true), b"");
+                assert_eq!(ph.try_skip_byte(), Err(OutOfBounds));
+                assert_eq!(ph.try_skip_bytes(2), Err(OutOfBounds));
+                assert_eq!(ph.try_slice(start..end), Ok(input.as_bytes()));
+                assert_eq!(ph.try_slice(end..past_end_byte), Err(OutOfBounds));
+                assert_eq!(ph.try_slice(past_end_byte..), Err(OutOfBounds));
+                assert_eq!(ph.try_slice(..past_end_byte), Err(OutOfBounds));
+                assert_eq!(ph.try_slice(..=end), Err(OutOfBounds));
+                assert_eq!(ph.try_slice(end..=end), Err(OutOfBounds));
+                assert_eq!(ph.slice_accepted(|ph| ph.try_skip_byte()).1, "");
+                assert_eq!(ph.slice_accepted_option(|ph| ph.try_skip_byte().ok()), None);
+                assert!(ph.is_at_utf8_boundary());
+                ph.skip_to_next_utf8_char_boundary();
+
+                let mut ch = ph.clone().into_char_oriented().unwrap();
+                ph = ph.clone().skip_into_char_oriented().into_byte_oriented();
+
+                assert!(ch.done());
+                assert_eq!(ch.leftover(), "");
+                assert_eq!(ch.upcoming_char(), None);
+                assert_eq!(ch.accept("a"), None);
+                assert_eq!(ch.accept(""), Some(""));
+                assert_eq!(ch.accept_char('a'), None);
+                assert_eq!(ch.accept_char_with(|_| true), None);
+                assert_eq!(ch.accept_until_char('a'), "");
+                assert_eq!(ch.accept_until_char_with(|_| true), "");
+                assert_eq!(ch.accept_until_whitespace(), "");
+                assert_eq!(ch.accept_whitespace(), None);
+                assert_eq!(ch.accept_zero_or_more_whitespace(), "");
+                assert_eq!(ch.accept_one_or_more_whitespace(), None);
+                #[cfg(feature = "icu")]
+                assert_eq!(ch.accept_rust_ident(), None);
+                let end = ch.mark();
+                assert_eq!(ch.try_slice(..end), Ok(input));
+                assert_eq!(ch.try_slice(end..past_end_char), Err(OutOfBounds));
+                assert_eq!(ch.try_slice(past_end_char..), Err(OutOfBounds));
+                assert_eq!(ch.try_slice(..past_end_char), Err(OutOfBounds));
+            }
+        }
+
+        // marks can point in the middle of a codepoint of another input
+        let ph = ParseHelper::new_char_oriented("\u{e9}");
+        let mut other = ParseHelper::new_char_oriented("ab");
+        other.accept_char('a');
+        assert_eq!(ph.try_slice(..other.mark()), Err(OutOfBounds));
+    }
+
     #[test]
     fn test_backup() {
         let mut x = ParseHelper::new_char_oriented("hello");
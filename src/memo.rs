@@ -0,0 +1,366 @@
+use alloc::collections::BTreeMap;
+use core::{
+    cell::{Cell, RefCell},
+    mem,
+};
+
+use crate::{boundary::private::BoundaryAssumption, ParseHelper};
+
+// the outcome of a rule: where it ended, and what it returned. `None` means it failed.
+type Outcome<V> = Option<(usize, V)>;
+
+// a rule that is currently being parsed at some position
+struct Seed<V> {
+    // set when the rule was used again at the same position while parsing it
+    left_recursive: bool,
+    // the best outcome so far, while growing a left recursive rule
+    best: Outcome<V>,
+}
+
+/// A cache of the outcomes of parsing rules, for packrat parsing with [`ParseHelper::memoized`].
+///
+/// Outcomes are keyed by a rule id and the position a rule started at, and hold where the
+/// rule ended together with the value it returned, or that it failed. With heavy backtracking,
+/// this makes sure every rule is parsed at most once at every position.
+///
+/// By default, outcomes are kept until the memo is dropped or [cleared](Self::clear).
+/// For large inputs, [`bounded`](Self::bounded) limits how many are kept.
+///
+/// A memo belongs to the input it is first used with. Using it with another input forgets
+/// everything that was stored for the previous one.
+pub struct Memo<V> {
+    // keyed by position first, so the outcomes earliest in the input are evicted first
+    outcomes: RefCell<BTreeMap<(usize, usize), Outcome<V>>>,
+    active: RefCell<BTreeMap<(usize, usize), Seed<V>>>,
+    capacity: Option<usize>,
+    // the address and length of the input the stored outcomes belong to
+    input: Cell<Option<(usize, usize)>>,
+}
+
+impl<V> Memo<V> {
+    /// Creates an empty memo that keeps every outcome.
+    pub fn new() -> Self {
+        Self {
+            outcomes: RefCell::new(BTreeMap::new()),
+            active: RefCell::new(BTreeMap::new()),
+            capacity: None,
+            input: Cell::new(None),
+        }
+    }
+
+    /// Creates an empty memo that keeps at most `capacity` outcomes.
+    ///
+    /// When it's full, the outcomes of rules that started earliest in the input are evicted
+    /// first, since parsers rarely backtrack far. Evicted rules are simply parsed again
+    /// when they're needed.
+    pub fn bounded(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Self::new()
+        }
+    }
+
+    /// The number of outcomes currently stored.
+    pub fn len(&self) -> usize {
+        self.outcomes.borrow().len()
+    }
+
+    /// Returns `true` if no outcomes are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget all stored outcomes, for example to reuse the memo for another input.
+    pub fn clear(&self) {
+        self.outcomes.borrow_mut().clear();
+        self.active.borrow_mut().clear();
+        self.input.set(None);
+    }
+
+    // binds the memo to `input`, forgetting everything stored for another input.
+    // Returns whether it was bound to `input` already.
+    fn bind<T: ?Sized>(&self, input: &T) -> bool {
+        let id = identify(input);
+        if self.input.get() == Some(id) {
+            return true;
+        }
+
+        self.clear();
+        self.input.set(Some(id));
+        false
+    }
+
+    fn store(&self, key: (usize, usize), outcome: Outcome<V>) {
+        let mut outcomes = self.outcomes.borrow_mut();
+        outcomes.insert(key, outcome);
+
+        if let Some(capacity) = self.capacity {
+            while outcomes.len() > capacity {
+                outcomes.pop_first();
+            }
+        }
+    }
+}
+
+// two inputs that are alive at the same time have the same address and length only when
+// they're the same input
+fn identify<T: ?Sized>(input: &T) -> (usize, usize) {
+    (
+        (input as *const T).cast::<()>() as usize,
+        mem::size_of_val(input),
+    )
+}
+
+impl<V> Default for Memo<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T: ?Sized, B> ParseHelper<'a, T, B>
+where
+    T: AsRef<[u8]>,
+    B: BoundaryAssumption,
+{
+    /// Parses `rule` with the closure, or reuses its outcome when it was parsed at this position
+    /// before.
+    ///
+    /// Like [`slice_accepted_option`](Self::slice_accepted_option), the parse helper resets to
+    /// where it was when the closure returns `None`. Rule ids only have to be unique within
+    /// one [`Memo`].
+    ///
+    /// Directly left recursive rules are supported, by growing a seed: the rule first fails
+    /// where it recurses into itself, and is then parsed again and again using its previous
+    /// outcome, for as long as that gets further.
+    ///
+    /// ```
+    /// use parse_helper::{Char, Memo, ParseHelper};
+    ///
+    /// const EXPR: usize = 0;
+    ///
+    /// // expr = expr '-' digit | digit
+    /// fn expr(ph: &mut ParseHelper<str, Char>, memo: &Memo<i64>) -> Option<i64> {
+    ///     ph.memoized(memo, EXPR, |ph| {
+    ///         let backup = ph.create_backup();
+    ///         if let Some(lhs) = expr(ph, memo) {
+    ///             if ph.accept_char('-').is_some() {
+    ///                 if let Some(rhs) = digit(ph) {
+    ///                     return Some(lhs - rhs);
+    ///                 }
+    ///             }
+    ///         }
+    ///         ph.restore_backup(backup);
+    ///         digit(ph)
+    ///     })
+    /// }
+    ///
+    /// fn digit(ph: &mut ParseHelper<str, Char>) -> Option<i64> {
+    ///     ph.accept_char_with(|c| c.is_ascii_digit())?.parse().ok()
+    /// }
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("7-2-1");
+    /// // left associative: (7 - 2) - 1
+    /// assert_eq!(expr(&mut ph, &Memo::new()), Some(4));
+    /// assert!(ph.done());
+    /// ```
+    pub fn memoized<V: Clone>(
+        &mut self,
+        memo: &Memo<V>,
+        rule: usize,
+        mut f: impl FnMut(&mut Self) -> Option<V>,
+    ) -> Option<V> {
+        let start = self.create_backup();
+        let key = (self.byte_position, rule);
+        memo.bind(self.input);
+
+        // recursing into a rule that is being parsed at this same position
+        if let Some(seed) = memo.active.borrow_mut().get_mut(&key) {
+            seed.left_recursive = true;
+            return self.apply_outcome(start, seed.best.clone());
+        }
+
+        let stored = memo.outcomes.borrow().get(&key).cloned();
+        if let Some(outcome) = stored {
+            return self.apply_outcome(start, outcome);
+        }
+
+        memo.active.borrow_mut().insert(
+            key,
+            Seed {
+                left_recursive: false,
+                best: None,
+            },
+        );
+
+        let mut outcome = self.run_rule(&start, &mut f);
+
+        let left_recursive = memo
+            .active
+            .borrow()
+            .get(&key)
+            .is_some_and(|seed| seed.left_recursive);
+        if left_recursive {
+            while let Some((end, _)) = outcome {
+                if let Some(seed) = memo.active.borrow_mut().get_mut(&key) {
+                    seed.best = outcome.clone();
+                }
+
+                self.restore_backup(start.create_backup());
+                match self.run_rule(&start, &mut f) {
+                    next @ Some((next_end, _)) if next_end > end => outcome = next,
+                    _ => break,
+                }
+            }
+        }
+
+        memo.active.borrow_mut().remove(&key);
+        // the memo may have been used for another input by `f` in the meantime
+        if memo.bind(self.input) {
+            memo.store(key, outcome.clone());
+        }
+
+        self.apply_outcome(start, outcome)
+    }
+
+    // runs a rule once, resetting to `start` when it fails
+    fn run_rule<V>(
+        &mut self,
+        start: &Self,
+        f: &mut impl FnMut(&mut Self) -> Option<V>,
+    ) -> Outcome<V> {
+        match f(self) {
+            Some(value) => Some((self.byte_position, value)),
+            None => {
+                self.restore_backup(start.create_backup());
+                None
+            }
+        }
+    }
+
+    fn apply_outcome<V>(&mut self, start: Self, outcome: Outcome<V>) -> Option<V> {
+        self.restore_backup(start);
+
+        let (end, value) = outcome?;
+        // outcomes always come from this input, but a stale one must never put the parse helper
+        // out of bounds or in the middle of a character
+        if !B::is_boundary(self.input.as_ref(), end) {
+            return None;
+        }
+        self.byte_position = end;
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use crate::{Char, Memo, ParseHelper};
+
+    const ATOM: usize = 0;
+    const SUM: usize = 1;
+
+    // sum = sum '+' atom | atom
+    // atom = '(' sum ')' | digit
+    struct Grammar<'m> {
+        memo: &'m Memo<u32>,
+        atom_calls: Cell<usize>,
+    }
+
+    impl Grammar<'_> {
+        fn sum(&self, ph: &mut ParseHelper<str, Char>) -> Option<u32> {
+            ph.memoized(self.memo, SUM, |ph| {
+                let backup = ph.create_backup();
+                if let Some(lhs) = self.sum(ph) {
+                    if ph.accept_char('+').is_some() {
+                        if let Some(rhs) = self.atom(ph) {
+                            return Some(lhs + rhs);
+                        }
+                    }
+                }
+                ph.restore_backup(backup);
+                self.atom(ph)
+            })
+        }
+
+        fn atom(&self, ph: &mut ParseHelper<str, Char>) -> Option<u32> {
+            ph.memoized(self.memo, ATOM, |ph| {
+                self.atom_calls.set(self.atom_calls.get() + 1);
+
+                let backup = ph.create_backup();
+                if ph.accept_char('(').is_some() {
+                    if let Some(res) = self.sum(ph) {
+                        if ph.accept_char(')').is_some() {
+                            return Some(res);
+                        }
+                    }
+                }
+                ph.restore_backup(backup);
+                ph.accept_char_with(|c| c.is_ascii_digit())?.parse().ok()
+            })
+        }
+    }
+
+    #[test]
+    fn left_recursion() {
+        let memo = Memo::new();
+        let grammar = Grammar {
+            memo: &memo,
+            atom_calls: Cell::new(0),
+        };
+
+        let mut ph = ParseHelper::new_char_oriented("1+(2+3)+4+");
+        assert_eq!(grammar.sum(&mut ph), Some(10));
+        assert_eq!(ph.leftover(), "+");
+
+        // every atom is parsed only once at every position
+        let atom_calls = grammar.atom_calls.get();
+        assert!(atom_calls <= ph.bytes_accepted() + 1);
+
+        let mut ph = ParseHelper::new_char_oriented("+");
+        memo.clear();
+        assert_eq!(grammar.sum(&mut ph), None);
+        assert_eq!(ph.leftover(), "+");
+    }
+
+    #[test]
+    fn bounded() {
+        let memo = Memo::bounded(2);
+        let grammar = Grammar {
+            memo: &memo,
+            atom_calls: Cell::new(0),
+        };
+
+        let mut ph = ParseHelper::new_char_oriented("((1+2)+3)+4");
+        assert_eq!(grammar.sum(&mut ph), Some(10));
+        assert!(ph.done());
+        assert_eq!(memo.len(), 2);
+    }
+
+    #[test]
+    fn reuse_on_shorter_input() {
+        let memo = Memo::new();
+        let rest = |ph: &mut ParseHelper<str, Char>| {
+            ph.memoized(&memo, 0, |ph| {
+                let mut n = 0;
+                while ph.accept_char_with(|_| true).is_some() {
+                    n += 1;
+                }
+                Some(n)
+            })
+        };
+
+        let mut ph = ParseHelper::new_char_oriented("ab");
+        assert_eq!(rest(&mut ph), Some(2));
+        assert!(ph.done());
+
+        // without clearing the memo first, the outcome for "ab" must not be applied to "a"
+        let mut ph = ParseHelper::new_char_oriented("a");
+        assert_eq!(rest(&mut ph), Some(1));
+        assert!(ph.done());
+        assert_eq!(memo.len(), 1);
+
+        memo.clear();
+        assert!(memo.is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+use core::fmt::{self, Debug, Display, Formatter};
+
+use crate::{Mark, ParseHelper};
+
+/// Error returned by [`ParseHelper::nested`] when parsers are nested deeper than the limit.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct NestingTooDeep<B> {
+    mark: Mark<B>,
+    limit: usize,
+}
+
+impl<B> NestingTooDeep<B> {
+    /// The position in the input where the limit was hit.
+    pub fn mark(&self) -> Mark<B> {
+        Mark::new(self.mark.byte_position())
+    }
+
+    /// The nesting limit that was exceeded.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl<B> Display for NestingTooDeep<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "nested more than {} levels deep at byte {}",
+            self.limit,
+            self.mark.byte_position()
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<B: Debug> std::error::Error for NestingTooDeep<B> {}
+
+impl<'a, T: ?Sized, B> ParseHelper<'a, T, B> {
+    /// How deep [`nested`](Self::nested) calls can go by default.
+    pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+    /// Runs a parsing closure one level deeper, or returns an error without running it when
+    /// that would be deeper than the [maximum depth](Self::set_max_depth).
+    ///
+    /// Wrap recursive calls in this, so deeply nested input returns an error instead of
+    /// overflowing the stack. The depth is part of the state of the parse helper, so it is
+    /// restored by [`restore_backup`](Self::restore_backup) too.
+    ///
+    /// ```
+    /// use parse_helper::{Char, NestingTooDeep, ParseHelper};
+    ///
+    /// // parens = '(' parens ')' | ''
+    /// fn parens(ph: &mut ParseHelper<str, Char>) -> Result<(), NestingTooDeep<Char>> {
+    ///     if ph.accept_char('(').is_some() {
+    ///         ph.nested(parens)??;
+    ///         ph.accept_char(')');
+    ///     }
+    ///     Ok(())
+    /// }
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("((()))");
+    /// ph.set_max_depth(3);
+    /// assert_eq!(parens(&mut ph), Ok(()));
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("(((())))");
+    /// ph.set_max_depth(3);
+    /// let err = parens(&mut ph).unwrap_err();
+    /// assert_eq!(err.mark().byte_position(), 4);
+    /// assert_eq!(err.limit(), 3);
+    /// ```
+    pub fn nested<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> Result<R, NestingTooDeep<B>> {
+        if self.depth >= self.max_depth {
+            return Err(NestingTooDeep {
+                mark: self.mark(),
+                limit: self.max_depth,
+            });
+        }
+
+        let depth = self.depth;
+        self.depth = depth.saturating_add(1);
+        let res = f(self);
+        // not a decrement: the closure may have restored a backup from another depth
+        self.depth = depth;
+
+        Ok(res)
+    }
+
+    /// The number of [`nested`](Self::nested) calls the parse helper is currently in.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The maximum depth of [`nested`](Self::nested) calls.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Sets the maximum depth of [`nested`](Self::nested) calls.
+    /// Defaults to [`DEFAULT_MAX_DEPTH`](Self::DEFAULT_MAX_DEPTH).
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Char, NestingTooDeep, ParseHelper};
+
+    fn list(ph: &mut ParseHelper<str, Char>) -> Result<usize, NestingTooDeep<Char>> {
+        let mut items = 0;
+        ph.accept_char('[');
+        while ph.accept_char(']').is_none() && !ph.done() {
+            if ph.upcoming_char() == Some('[') {
+                ph.nested(list)??;
+            } else {
+                ph.accept_until_char_with(|c| c == ',' || c == ']' || c == '[');
+            }
+            ph.accept_char(',');
+            items += 1;
+        }
+        Ok(items)
+    }
+
+    #[test]
+    fn hostile_input() {
+        let input = "[".repeat(1_000_000);
+        let mut ph = ParseHelper::new_char_oriented(input.as_str());
+
+        let err = list(&mut ph).unwrap_err();
+        assert_eq!(err.limit(), ParseHelper::<str, Char>::DEFAULT_MAX_DEPTH);
+        assert_eq!(err.mark().byte_position(), 257);
+        assert_eq!(
+            err.to_string(),
+            "nested more than 256 levels deep at byte 257"
+        );
+    }
+
+    #[test]
+    fn depth_is_restored() {
+        let mut ph = ParseHelper::new_char_oriented("[a,[b]]");
+        let backup = ph.create_backup();
+
+        ph.nested(|ph| {
+            assert_eq!(ph.depth(), 1);
+            ph.nested(|ph| {
+                assert_eq!(ph.depth(), 2);
+                ph.restore_backup(backup);
+                assert_eq!(ph.depth(), 0);
+            })
+        })
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(ph.depth(), 0);
+        assert_eq!(list(&mut ph), Ok(2));
+        assert!(ph.done());
+    }
+}
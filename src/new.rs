@@ -1,4 +1,4 @@
-use std::marker::PhantomData;
+use core::marker::PhantomData;
 
 use crate::{Byte, Char, ParseHelper};
 
@@ -11,18 +11,30 @@ where
     }
 }
 
-impl<'a, T: ?Sized> ParseHelper<'a, T, Byte> {
-    /// Creates a new [`ParseHelper`] that assumes
-    /// steps can be taken one byte at a time.
-    pub fn new_byte_oriented(input: &'a T) -> Self {
+impl<'a, T: ?Sized, B> ParseHelper<'a, T, B> {
+    // creates a parse helper at the start of the input. Only valid if the start of the input
+    // is a boundary according to `B`, which is always true for `Byte` and `Char`.
+    pub(crate) const fn with_boundary_assumption(input: &'a T) -> Self {
         Self {
             input,
             byte_position: 0,
             boundary_assumption: PhantomData,
+            depth: 0,
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            #[cfg(feature = "trace")]
+            tracer: None,
         }
     }
 }
 
+impl<'a, T: ?Sized> ParseHelper<'a, T, Byte> {
+    /// Creates a new [`ParseHelper`] that assumes
+    /// steps can be taken one byte at a time.
+    pub const fn new_byte_oriented(input: &'a T) -> Self {
+        Self::with_boundary_assumption(input)
+    }
+}
+
 impl<'a, T: ?Sized> ParseHelper<'a, T, Char>
 where
     T: AsRef<str>,
@@ -30,12 +42,8 @@ where
     /// Creates a new [`ParseHelper`] that assumes
     /// steps can only be taken one utf8 codepoint at a time,
     /// and we can never end up between codepoints
-    pub fn new_char_oriented(input: &'a T) -> Self {
-        Self {
-            input,
-            byte_position: 0,
-            boundary_assumption: PhantomData,
-        }
+    pub const fn new_char_oriented(input: &'a T) -> Self {
+        Self::with_boundary_assumption(input)
     }
 }
 
@@ -0,0 +1,646 @@
+use core::{
+    fmt::{self, Debug, Display, Formatter},
+    marker::PhantomData,
+    ops::{Deref, Range},
+    ptr,
+};
+
+use crate::{
+    boundary::private::BoundaryAssumption, Byte, Char, LineColumn, Mark, OutOfBounds, ParseHelper,
+    Span,
+};
+
+/// A parse helper that owns its input, like an `Arc<str>`, `Rc<[u8]>` or `String`,
+/// instead of borrowing it.
+///
+/// Without a lifetime, it can be stored in structs and sent to other threads or async tasks.
+/// Accepted parts of the input are returned as [`Fragment`]s, which keep a clone of the
+/// input together with a range. For `Arc` and `Rc`, that's cheap.
+///
+/// Marks are the same as the ones of a [`ParseHelper`], and can be used with either.
+/// Every method of the borrowed parse helper is available through
+/// [`with_borrowed`](Self::with_borrowed).
+///
+/// ```
+/// use parse_helper::OwnedParseHelper;
+/// use std::sync::Arc;
+///
+/// let mut ph = OwnedParseHelper::new_char_oriented(Arc::<str>::from("key = value"));
+/// let key = ph.accept_until_whitespace();
+/// ph.accept_zero_or_more_whitespace();
+/// ph.accept_char('=');
+/// ph.accept_zero_or_more_whitespace();
+///
+/// let value = std::thread::spawn(move || ph.leftover()).join().unwrap();
+/// assert_eq!(key, "key");
+/// assert_eq!(value, "value");
+/// ```
+pub struct OwnedParseHelper<S, B> {
+    input: S,
+    byte_position: usize,
+    boundary_assumption: PhantomData<B>,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl<S: Clone, B> Clone for OwnedParseHelper<S, B> {
+    fn clone(&self) -> Self {
+        Self {
+            input: self.input.clone(),
+            byte_position: self.byte_position,
+            boundary_assumption: PhantomData,
+            depth: self.depth,
+            max_depth: self.max_depth,
+        }
+    }
+}
+
+impl<S: Deref, B> OwnedParseHelper<S, B> {
+    // creates a parse helper at the start of the input, see `ParseHelper::with_boundary_assumption`
+    fn with_boundary_assumption(input: S) -> Self {
+        Self {
+            input,
+            byte_position: 0,
+            boundary_assumption: PhantomData,
+            depth: 0,
+            max_depth: ParseHelper::<S::Target, B>::DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Returns the input of the parse helper.
+    pub fn into_inner(self) -> S {
+        self.input
+    }
+}
+
+impl<S: Deref, B> OwnedParseHelper<S, B>
+where
+    S::Target: AsRef<[u8]>,
+    B: BoundaryAssumption,
+{
+    /// Runs a closure on a borrowed [`ParseHelper`] at the current position. Anything it
+    /// accepts is accepted by this parse helper too.
+    ///
+    /// ```
+    /// use parse_helper::OwnedParseHelper;
+    ///
+    /// let mut ph = OwnedParseHelper::new_char_oriented(String::from("ab\ncd"));
+    /// let line = ph.with_borrowed(|ph| {
+    ///     ph.accept("ab\nc");
+    ///     ph.line_column(ph.mark()).line
+    /// });
+    /// assert_eq!(line, 2);
+    /// assert_eq!(ph.leftover(), "d");
+    /// ```
+    ///
+    /// If the closure replaces the parse helper with one over another input, nothing it
+    /// accepted is kept.
+    pub fn with_borrowed<R>(&mut self, f: impl FnOnce(&mut ParseHelper<S::Target, B>) -> R) -> R {
+        let mut ph = self.borrowed();
+        let res = f(&mut ph);
+
+        if !ptr::eq(ph.input, &*self.input) {
+            return res;
+        }
+
+        let (byte_position, depth, max_depth) = (ph.byte_position, ph.depth, ph.max_depth);
+        self.byte_position = byte_position;
+        self.depth = depth;
+        self.max_depth = max_depth;
+
+        res
+    }
+
+    /// Returns a borrowed [`ParseHelper`] at the current position.
+    ///
+    /// When the input doesn't deref to the same value every time and the current position
+    /// isn't valid in what it derefs to now, the borrowed parse helper is at the end of it.
+    pub fn borrowed(&self) -> ParseHelper<'_, S::Target, B> {
+        let input = &*self.input;
+        let mut ph = ParseHelper::with_boundary_assumption(input);
+        ph.byte_position = if B::is_boundary(input.as_ref(), self.byte_position) {
+            self.byte_position
+        } else {
+            input.as_ref().len()
+        };
+        ph.depth = self.depth;
+        ph.max_depth = self.max_depth;
+        ph
+    }
+}
+
+impl<S, B> OwnedParseHelper<S, B> {
+    /// Same as clone, but this can help show intent (together with
+    /// [`restore_backup`](Self::restore_backup))
+    pub fn create_backup(&self) -> Self
+    where
+        S: Clone,
+    {
+        self.clone()
+    }
+
+    /// simply overwrites self. However, can be nice to show intent.
+    pub fn restore_backup(&mut self, other: Self) {
+        *self = other;
+    }
+
+    /// Creates a mark at the current position of the parse helper.
+    pub fn mark(&self) -> Mark<B> {
+        Mark::new(self.byte_position)
+    }
+
+    /// Returns how many bytes have been accepted sofar.
+    pub fn bytes_accepted(&self) -> usize {
+        self.byte_position
+    }
+}
+
+impl<S: Deref + Clone, B> OwnedParseHelper<S, B>
+where
+    S::Target: AsRef<[u8]>,
+    B: BoundaryAssumption,
+{
+    fn fragment(&self, start: usize) -> Fragment<S, B> {
+        Fragment {
+            source: self.input.clone(),
+            start,
+            end: self.byte_position,
+            boundary_assumption: PhantomData,
+        }
+    }
+
+    /// Like [`ParseHelper::slice_accepted`], but returns what the closure accepted as a
+    /// [`Fragment`].
+    pub fn slice_accepted<P>(
+        &mut self,
+        closure: impl FnOnce(&mut ParseHelper<S::Target, B>) -> P,
+    ) -> (P, Fragment<S, B>) {
+        let start = self.byte_position;
+        let res = self.with_borrowed(closure);
+        (res, self.fragment(start))
+    }
+
+    /// Like [`ParseHelper::slice_accepted_option`], but returns what the closure accepted as a
+    /// [`Fragment`]. The parse helper resets to where it was when the closure returns `None`.
+    pub fn slice_accepted_option(
+        &mut self,
+        closure: impl FnOnce(&mut ParseHelper<S::Target, B>) -> Option<()>,
+    ) -> Option<Fragment<S, B>> {
+        let old = self.create_backup();
+        match self.slice_accepted(closure) {
+            (Some(()), fragment) => Some(fragment),
+            (None, _) => {
+                self.restore_backup(old);
+                None
+            }
+        }
+    }
+
+    // accepts whatever the closure accepted if it returns true
+    fn accept_if(
+        &mut self,
+        f: impl FnOnce(&mut ParseHelper<S::Target, B>) -> bool,
+    ) -> Option<Fragment<S, B>> {
+        self.slice_accepted_option(|ph| f(ph).then_some(()))
+    }
+}
+
+impl<S: Deref, B> OwnedParseHelper<S, B>
+where
+    S::Target: AsRef<[u8]>,
+    B: BoundaryAssumption,
+{
+    /// Returns `true` if the end of the input has been reached.
+    pub fn done(&self) -> bool {
+        self.borrowed().done()
+    }
+
+    /// Returns how many bytes are left to parse
+    pub fn bytes_left(&self) -> usize {
+        self.borrowed().bytes_left()
+    }
+
+    /// returns the next byte that is going to be parsed.
+    pub fn upcoming_byte(&self) -> Option<u8> {
+        self.borrowed().upcoming_byte()
+    }
+}
+
+impl<S: Deref> OwnedParseHelper<S, Char>
+where
+    S::Target: AsRef<str>,
+{
+    /// Creates a new [`OwnedParseHelper`] that assumes
+    /// steps can only be taken one utf8 codepoint at a time,
+    /// and we can never end up between codepoints
+    pub fn new_char_oriented(input: S) -> Self {
+        Self::with_boundary_assumption(input)
+    }
+}
+
+impl<S: Deref> OwnedParseHelper<S, Byte> {
+    /// Creates a new [`OwnedParseHelper`] that assumes
+    /// steps can be taken one byte at a time.
+    pub fn new_byte_oriented(input: S) -> Self {
+        Self::with_boundary_assumption(input)
+    }
+}
+
+impl<S: Deref + Clone> OwnedParseHelper<S, Char>
+where
+    S::Target: AsRef<str> + AsRef<[u8]>,
+{
+    /// Returns the remaining string, the part that has not yet been accepted
+    pub fn leftover(&self) -> Fragment<S, Char> {
+        let mut ph = self.clone();
+        ph.byte_position = AsRef::<[u8]>::as_ref(&*self.input).len();
+        ph.fragment(self.byte_position)
+    }
+
+    /// returns the next character to be accepted
+    pub fn upcoming_char(&self) -> Option<char> {
+        self.borrowed().upcoming_char()
+    }
+
+    /// Returns the line and column of a mark in the input.
+    pub fn line_column(&self, mark: Mark<Char>) -> LineColumn {
+        self.borrowed().line_column(mark)
+    }
+
+    /// Accepts a sequence of string-like values from the input.
+    pub fn accept(&mut self, str: impl AsRef<str>) -> Option<Fragment<S, Char>> {
+        self.accept_if(|ph| ph.accept(str).is_some())
+    }
+
+    /// Accepts a character if the passed closure evaluates to true.
+    pub fn accept_char_with(&mut self, f: impl Fn(char) -> bool) -> Option<Fragment<S, Char>> {
+        self.accept_if(|ph| ph.accept_char_with(f).is_some())
+    }
+
+    /// accepts a single char from the input.
+    pub fn accept_char(&mut self, c: char) -> Option<Fragment<S, Char>> {
+        self.accept_if(|ph| ph.accept_char(c).is_some())
+    }
+
+    /// Accepts until the closure matches the current character.
+    ///
+    /// Returns what's accepted until then, but not including the matching character.
+    pub fn accept_until_char_with(&mut self, f: impl Fn(char) -> bool) -> Fragment<S, Char> {
+        self.slice_accepted(|ph| {
+            ph.accept_until_char_with(f);
+        })
+        .1
+    }
+
+    /// Accepts until a specific character is encountered
+    ///
+    /// Returns what's accepted until then, but not including the matching character.
+    pub fn accept_until_char(&mut self, c: char) -> Fragment<S, Char> {
+        self.accept_until_char_with(|x| x == c)
+    }
+
+    /// Accepts until whitespace is encountered
+    ///
+    /// Returns what's accepted until then, but not including the whitespace
+    pub fn accept_until_whitespace(&mut self) -> Fragment<S, Char> {
+        self.accept_until_char_with(char::is_whitespace)
+    }
+
+    /// Accepts a single whitespace character.
+    pub fn accept_whitespace(&mut self) -> Option<Fragment<S, Char>> {
+        self.accept_char_with(char::is_whitespace)
+    }
+
+    /// Accepts a sequence of zero or more whitespace characters.
+    pub fn accept_zero_or_more_whitespace(&mut self) -> Fragment<S, Char> {
+        self.accept_until_char_with(|x| !x.is_whitespace())
+    }
+
+    /// Accepts a sequence of one or more whitespace characters.
+    pub fn accept_one_or_more_whitespace(&mut self) -> Option<Fragment<S, Char>> {
+        self.accept_if(|ph| ph.accept_one_or_more_whitespace().is_some())
+    }
+
+    /// Like [`ParseHelper::try_slice`], but returns a [`Fragment`].
+    ///
+    /// ```
+    /// use parse_helper::{OutOfBounds, OwnedParseHelper};
+    /// use std::rc::Rc;
+    ///
+    /// let mut ph = OwnedParseHelper::new_char_oriented(Rc::<str>::from("ab cd"));
+    /// let start = ph.mark();
+    /// ph.accept_until_whitespace();
+    /// let end = ph.mark();
+    ///
+    /// assert_eq!(ph.try_slice(start..end).unwrap(), "ab");
+    /// assert!(ph.try_slice(end..start).is_err());
+    /// ```
+    pub fn try_slice(&self, range: Range<Mark<Char>>) -> Result<Fragment<S, Char>, OutOfBounds> {
+        let (start, end) = (range.start.byte_position(), range.end.byte_position());
+        self.borrowed().try_slice(range)?;
+
+        Ok(Fragment {
+            source: self.input.clone(),
+            start,
+            end,
+            boundary_assumption: PhantomData,
+        })
+    }
+}
+
+impl<S: Deref + Clone> OwnedParseHelper<S, Byte>
+where
+    S::Target: AsRef<[u8]>,
+{
+    /// Returns the remaining bytes, the part that has not yet been accepted
+    pub fn leftover(&self) -> Fragment<S, Byte> {
+        let mut ph = self.clone();
+        ph.byte_position = self.input.as_ref().len();
+        ph.fragment(self.byte_position)
+    }
+
+    /// Accept a single byte. Returns whether it accepted the byte.
+    pub fn accept_byte(&mut self, c: u8) -> bool {
+        self.with_borrowed(|ph| ph.accept_byte(c))
+    }
+
+    /// Accepts a byte if the passed closure evaluates to true, and returns it.
+    pub fn accept_byte_with(&mut self, f: impl Fn(u8) -> bool) -> Option<u8> {
+        self.with_borrowed(|ph| ph.accept_byte_with(f))
+    }
+
+    /// Accepts a sequence of bytes from the input.
+    pub fn accept(&mut self, bytes: impl AsRef<[u8]>) -> Option<Fragment<S, Byte>> {
+        self.accept_if(|ph| ph.accept(bytes).is_some())
+    }
+
+    /// Accepts until the closure matches the current byte.
+    ///
+    /// Returns what's accepted until then, but not including the matching byte.
+    pub fn accept_until_byte_with(&mut self, f: impl Fn(u8) -> bool) -> Fragment<S, Byte> {
+        self.slice_accepted(|ph| {
+            ph.accept_until_byte_with(f);
+        })
+        .1
+    }
+
+    /// Accepts until a specific byte is encountered
+    ///
+    /// Returns what's accepted until then, but not including the matching byte.
+    pub fn accept_until_byte(&mut self, c: u8) -> Fragment<S, Byte> {
+        self.accept_until_byte_with(|x| x == c)
+    }
+
+    /// Like [`ParseHelper::try_slice`], but returns a [`Fragment`].
+    pub fn try_slice(&self, range: Range<Mark<Byte>>) -> Result<Fragment<S, Byte>, OutOfBounds> {
+        let (start, end) = (range.start.byte_position(), range.end.byte_position());
+        self.borrowed().try_slice(range)?;
+
+        Ok(Fragment {
+            source: self.input.clone(),
+            start,
+            end,
+            boundary_assumption: PhantomData,
+        })
+    }
+}
+
+/// A part of the input of an [`OwnedParseHelper`], which keeps a clone of the input
+/// together with the range it covers.
+///
+/// It derefs to `str` for [`Char`] oriented parse helpers and to `[u8]` for [`Byte`]
+/// oriented ones.
+pub struct Fragment<S, B> {
+    source: S,
+    start: usize,
+    end: usize,
+    boundary_assumption: PhantomData<B>,
+}
+
+impl<S, B> Fragment<S, B> {
+    /// The input this is a part of.
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// The span of the input this fragment covers.
+    pub fn span(&self) -> Span<B> {
+        Span::new(Mark::new(self.start), Mark::new(self.end))
+    }
+}
+
+impl<S: Clone, B> Clone for Fragment<S, B> {
+    fn clone(&self) -> Self {
+        Self {
+            source: self.source.clone(),
+            start: self.start,
+            end: self.end,
+            boundary_assumption: PhantomData,
+        }
+    }
+}
+
+impl<S: Deref> Deref for Fragment<S, Char>
+where
+    S::Target: AsRef<str>,
+{
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        // fragments are only created from ranges that were checked to be in bounds
+        AsRef::<str>::as_ref(&*self.source)
+            .get(self.start..self.end)
+            .unwrap_or_default()
+    }
+}
+
+impl<S: Deref> Deref for Fragment<S, Byte>
+where
+    S::Target: AsRef<[u8]>,
+{
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // fragments are only created from ranges that were checked to be in bounds
+        self.source
+            .as_ref()
+            .get(self.start..self.end)
+            .unwrap_or_default()
+    }
+}
+
+impl<S: Deref> Debug for Fragment<S, Char>
+where
+    S::Target: AsRef<str>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<S: Deref> Debug for Fragment<S, Byte>
+where
+    S::Target: AsRef<[u8]>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<S: Deref> Display for Fragment<S, Char>
+where
+    S::Target: AsRef<str>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+// fragments compare by their contents, not by their position in the input
+impl<S, B> PartialEq for Fragment<S, B>
+where
+    Self: Deref,
+    <Self as Deref>::Target: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<S, B> Eq for Fragment<S, B>
+where
+    Self: Deref,
+    <Self as Deref>::Target: Eq,
+{
+}
+
+impl<S: Deref> PartialEq<str> for Fragment<S, Char>
+where
+    S::Target: AsRef<str>,
+{
+    fn eq(&self, other: &str) -> bool {
+        &**self == other
+    }
+}
+
+impl<S: Deref> PartialEq<&str> for Fragment<S, Char>
+where
+    S::Target: AsRef<str>,
+{
+    fn eq(&self, other: &&str) -> bool {
+        &**self == *other
+    }
+}
+
+impl<S: Deref> PartialEq<[u8]> for Fragment<S, Byte>
+where
+    S::Target: AsRef<[u8]>,
+{
+    fn eq(&self, other: &[u8]) -> bool {
+        &**self == other
+    }
+}
+
+impl<S: Deref> PartialEq<&[u8]> for Fragment<S, Byte>
+where
+    S::Target: AsRef<[u8]>,
+{
+    fn eq(&self, other: &&[u8]) -> bool {
+        &**self == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{rc::Rc, sync::Arc, vec::Vec};
+    use core::{cell::Cell, ops::Deref};
+
+    use crate::{OwnedParseHelper, ParseHelper};
+
+    // derefs to whatever it was last set to
+    struct Swappable(Rc<Cell<&'static str>>);
+
+    impl Deref for Swappable {
+        type Target = str;
+
+        fn deref(&self) -> &str {
+            self.0.get()
+        }
+    }
+
+    #[test]
+    fn marks_are_interchangeable() {
+        let input: Arc<str> = Arc::from("fn main() {}");
+        let mut owned = OwnedParseHelper::new_char_oriented(input.clone());
+        let mut borrowed = ParseHelper::new_char_oriented(&*input);
+
+        let start = owned.mark();
+        let name = owned
+            .slice_accepted_option(|ph| {
+                ph.accept("fn")?;
+                ph.accept_whitespace()?;
+                ph.accept_until_char('(');
+                Some(())
+            })
+            .unwrap();
+        assert_eq!(name, "fn main");
+        assert_eq!(owned.accept("{"), None);
+        assert_eq!(owned.accept("()").unwrap(), "()");
+        assert!(Arc::ptr_eq(name.source(), &input));
+
+        borrowed.accept_until_char('{');
+        assert_eq!(borrowed.slice(start..owned.mark()), "fn main()");
+        assert_eq!(
+            owned.try_slice(start..borrowed.mark()).unwrap(),
+            "fn main() "
+        );
+        assert_eq!(name.span().len(), 7);
+        assert_eq!(owned.leftover(), " {}");
+    }
+
+    #[test]
+    fn bytes() {
+        let mut ph = OwnedParseHelper::new_byte_oriented(Rc::<[u8]>::from(&b"\x01ab\0cd"[..]));
+        assert!(ph.accept_byte(1));
+        assert_eq!(ph.accept_until_byte(0), b"ab".as_slice());
+        assert_eq!(ph.accept_byte_with(|b| b == 0), Some(0));
+        assert_eq!(ph.bytes_left(), 2);
+        assert_eq!(ph.accept(b"cd").unwrap(), b"cd".as_slice());
+        assert!(ph.done());
+
+        let ph = OwnedParseHelper::new_byte_oriented(Vec::from("é"));
+        assert!(ph.try_slice(ph.mark()..ph.leftover().span().end()).is_ok());
+    }
+
+    #[test]
+    fn borrowed_over_another_input() {
+        let mut ph = OwnedParseHelper::new_char_oriented(Rc::<str>::from("ab"));
+        let accepted = ph.with_borrowed(|ph| {
+            *ph = ParseHelper::new_char_oriented("a longer input");
+            ph.accept("a longer").is_some()
+        });
+        assert!(accepted);
+        assert_eq!(ph.bytes_accepted(), 0);
+        assert_eq!(ph.leftover(), "ab");
+    }
+
+    #[test]
+    fn inconsistent_deref() {
+        let input = Rc::new(Cell::new("aéa"));
+        let mut ph = OwnedParseHelper::new_char_oriented(Swappable(input.clone()));
+        ph.with_borrowed(|ph| {
+            ph.accept("aé");
+        });
+        assert_eq!(ph.bytes_accepted(), 3);
+
+        // there's no boundary at 3 in "éa"
+        input.set("éa");
+        assert!(ph.done());
+        assert_eq!(ph.borrowed().upcoming_char(), None);
+
+        input.set("a");
+        assert!(ph.done());
+        input.set("abcd");
+        assert_eq!(ph.borrowed().upcoming_char(), Some('d'));
+    }
+}
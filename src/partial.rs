@@ -0,0 +1,487 @@
+use core::{
+    fmt::{self, Display, Formatter},
+    marker::PhantomData,
+    str,
+};
+
+use crate::{Byte, Char, Mark, ParseHelper};
+
+/// Why a method of a [`PartialParseHelper`] didn't accept anything.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PartialError {
+    /// The input doesn't match, and more input wouldn't change that.
+    Rejected,
+    /// The input ran out before it was clear whether it matches.
+    Incomplete {
+        /// How many more bytes are needed at least, if that is known.
+        needed: Option<usize>,
+    },
+}
+
+impl PartialError {
+    /// Returns `true` if more input is needed.
+    pub fn is_incomplete(&self) -> bool {
+        matches!(self, Self::Incomplete { .. })
+    }
+}
+
+impl Display for PartialError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rejected => write!(f, "input rejected"),
+            Self::Incomplete { needed: Some(n) } => write!(f, "at least {n} more bytes needed"),
+            Self::Incomplete { needed: None } => write!(f, "more input needed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PartialError {}
+
+/// A parse helper for input that arrives in chunks, like messages of a network protocol.
+///
+/// When the input ends before it's clear whether something matches, methods return
+/// [`PartialError::Incomplete`] instead of failing like a [`ParseHelper`] would. Save a
+/// [`Mark`] at the start of a message and [`resume`](Self::resume) from there once more input
+/// arrived. After [`end_of_input`](Self::end_of_input), no more input is expected, and running
+/// out of input simply rejects.
+///
+/// Methods return a [`Result`], so they can be chained with `?`.
+///
+/// ```
+/// use parse_helper::{Char, PartialError, PartialParseHelper};
+///
+/// // a message is "LEN " followed by a decimal number and a newline
+/// fn message(ph: &mut PartialParseHelper<[u8], Char>) -> Result<u32, PartialError> {
+///     ph.attempt(|ph| {
+///         ph.accept("LEN ")?;
+///         let digits = ph.accept_until_char('\n')?;
+///         ph.accept_char('\n')?;
+///         digits.parse().map_err(|_| PartialError::Rejected)
+///     })
+/// }
+///
+/// let mut buffer = b"LE".to_vec();
+/// let mut ph = PartialParseHelper::new_char_oriented(buffer.as_slice());
+/// let start = ph.mark();
+/// assert_eq!(message(&mut ph), Err(PartialError::Incomplete { needed: Some(2) }));
+///
+/// buffer.extend_from_slice(b"N 12");
+/// let mut ph = PartialParseHelper::resume(buffer.as_slice(), start).unwrap();
+/// assert_eq!(message(&mut ph), Err(PartialError::Incomplete { needed: None }));
+///
+/// buffer.extend_from_slice(b"3\nLEN x\n");
+/// let mut ph = PartialParseHelper::resume(buffer.as_slice(), start).unwrap();
+/// assert_eq!(message(&mut ph), Ok(123));
+/// assert_eq!(message(&mut ph), Err(PartialError::Rejected));
+/// ```
+pub struct PartialParseHelper<'a, T: ?Sized, B> {
+    // always byte oriented, since chunks of text may end halfway a character
+    inner: ParseHelper<'a, T, Byte>,
+    complete: bool,
+    boundary_assumption: PhantomData<B>,
+}
+
+impl<'a, T: ?Sized, B> Clone for PartialParseHelper<'a, T, B> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            complete: self.complete,
+            boundary_assumption: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: ?Sized> PartialParseHelper<'a, T, Byte>
+where
+    T: AsRef<[u8]>,
+{
+    /// Creates a new [`PartialParseHelper`] that assumes
+    /// steps can be taken one byte at a time.
+    pub fn new_byte_oriented(input: &'a T) -> Self {
+        Self::new(input)
+    }
+}
+
+impl<'a, T: ?Sized> PartialParseHelper<'a, T, Char>
+where
+    T: AsRef<[u8]>,
+{
+    /// Creates a new [`PartialParseHelper`] that assumes steps can only be taken one utf8
+    /// codepoint at a time.
+    ///
+    /// The input is given as bytes, since a chunk of text may end halfway a character.
+    /// Characters that are not valid utf8 are rejected.
+    pub fn new_char_oriented(input: &'a T) -> Self {
+        Self::new(input)
+    }
+}
+
+impl<'a, T: ?Sized, B> PartialParseHelper<'a, T, B>
+where
+    T: AsRef<[u8]>,
+{
+    fn new(input: &'a T) -> Self {
+        Self {
+            inner: ParseHelper::new_byte_oriented(input),
+            complete: false,
+            boundary_assumption: PhantomData,
+        }
+    }
+
+    /// Continues parsing at a mark of an earlier parse helper, on `input` that has more bytes
+    /// appended to the input of that earlier parse helper.
+    ///
+    /// Returns `None` if the mark lies past the end of `input`.
+    pub fn resume(input: &'a T, from: Mark<B>) -> Option<Self> {
+        let mut res = Self::new(input);
+        res.inner.try_skip_bytes(from.byte_position()).ok()?;
+        Some(res)
+    }
+
+    /// Tells the parse helper that no more input will follow. From then on, running out of
+    /// input rejects instead of returning [`PartialError::Incomplete`].
+    pub fn end_of_input(&mut self) {
+        self.complete = true;
+    }
+
+    /// Returns `true` if [`end_of_input`](Self::end_of_input) was called.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Returns `true` if all input has been accepted, and no more input will follow.
+    pub fn done(&self) -> bool {
+        self.complete && self.inner.done()
+    }
+
+    /// Creates a mark at the current position, to [`resume`](Self::resume) from.
+    pub fn mark(&self) -> Mark<B> {
+        Mark::new(self.inner.byte_position)
+    }
+
+    /// Returns how many bytes have been accepted sofar.
+    pub fn bytes_accepted(&self) -> usize {
+        self.inner.bytes_accepted()
+    }
+
+    /// Returns the remaining bytes, the part that has not yet been accepted
+    pub fn leftover(&self) -> &'a [u8] {
+        self.inner.leftover()
+    }
+
+    /// Same as clone, but this can help show intent (together with
+    /// [`restore_backup`](Self::restore_backup))
+    pub fn create_backup(&self) -> Self {
+        self.clone()
+    }
+
+    /// simply overwrites self. However, can be nice to show intent.
+    pub fn restore_backup(&mut self, other: Self) {
+        *self = other;
+    }
+
+    /// Runs a parsing closure, and resets to where the parse helper was before when it
+    /// returns an error.
+    pub fn attempt<R, E>(&mut self, f: impl FnOnce(&mut Self) -> Result<R, E>) -> Result<R, E> {
+        let old = self.create_backup();
+        let res = f(self);
+        if res.is_err() {
+            self.restore_backup(old);
+        }
+        res
+    }
+
+    // the error for when the input ended while `needed` more bytes were expected
+    fn ran_out(&self, needed: Option<usize>) -> PartialError {
+        if self.complete {
+            PartialError::Rejected
+        } else {
+            PartialError::Incomplete { needed }
+        }
+    }
+
+    // accepts `bytes`, or tells whether more input could still make it match
+    fn accept_bytes(&mut self, bytes: &[u8]) -> Result<&'a [u8], PartialError> {
+        let leftover = self.inner.leftover();
+
+        if leftover.len() < bytes.len() {
+            return if bytes.starts_with(leftover) {
+                Err(self.ran_out(Some(bytes.len().saturating_sub(leftover.len()))))
+            } else {
+                Err(PartialError::Rejected)
+            };
+        }
+
+        self.inner.accept(bytes).ok_or(PartialError::Rejected)
+    }
+
+    // skips `len` bytes, that are known to be there
+    fn advance(&mut self, len: usize) -> &'a [u8] {
+        self.inner.accept_n_bytes(len).unwrap_or_default()
+    }
+}
+
+impl<'a, T: ?Sized> PartialParseHelper<'a, T, Byte>
+where
+    T: AsRef<[u8]>,
+{
+    /// returns the next byte that is going to be parsed.
+    pub fn upcoming_byte(&self) -> Result<u8, PartialError> {
+        self.inner.upcoming_byte().ok_or(self.ran_out(Some(1)))
+    }
+
+    /// Accepts exactly `n` bytes.
+    pub fn accept_n_bytes(&mut self, n: usize) -> Result<&'a [u8], PartialError> {
+        let left = self.inner.bytes_left();
+        if left < n {
+            return Err(self.ran_out(Some(n.saturating_sub(left))));
+        }
+
+        self.inner.accept_n_bytes(n).ok_or(PartialError::Rejected)
+    }
+
+    /// accepts a single byte from the input
+    pub fn accept_byte(&mut self, c: u8) -> Result<u8, PartialError> {
+        self.accept_byte_with(|x| x == c)
+    }
+
+    /// Accepts a byte if the passed closure evaluates to true.
+    pub fn accept_byte_with(&mut self, f: impl Fn(u8) -> bool) -> Result<u8, PartialError> {
+        let b = self.upcoming_byte()?;
+        if f(b) {
+            self.advance(1);
+            Ok(b)
+        } else {
+            Err(PartialError::Rejected)
+        }
+    }
+
+    /// accepts a sequence of bytes-like values from the input
+    ///
+    /// When the input ends before it, but what's there matches so far, more input is needed.
+    pub fn accept(&mut self, bytes: impl AsRef<[u8]>) -> Result<&'a [u8], PartialError> {
+        self.accept_bytes(bytes.as_ref())
+    }
+
+    /// Accepts until the closure matches the current byte.
+    ///
+    /// Returns what's accepted until then, but not including the matching byte. When no byte
+    /// matches before the input ends, more input is needed, since the next chunk might still
+    /// continue what would be accepted.
+    pub fn accept_until_byte_with(
+        &mut self,
+        f: impl Fn(u8) -> bool,
+    ) -> Result<&'a [u8], PartialError> {
+        match self.inner.leftover().iter().position(|&b| f(b)) {
+            Some(len) => Ok(self.advance(len)),
+            None if self.complete => Ok(self.advance(self.inner.bytes_left())),
+            None => Err(PartialError::Incomplete { needed: None }),
+        }
+    }
+
+    /// Accepts until a specific byte is encountered
+    ///
+    /// Returns what's accepted until then, but not including the matching byte.
+    pub fn accept_until_byte(&mut self, c: u8) -> Result<&'a [u8], PartialError> {
+        self.accept_until_byte_with(|x| x == c)
+    }
+}
+
+impl<'a, T: ?Sized> PartialParseHelper<'a, T, Char>
+where
+    T: AsRef<[u8]>,
+{
+    /// returns the next character to be accepted, together with how many bytes it takes up.
+    fn next_char(&self, bytes: &[u8]) -> Result<(char, usize), PartialError> {
+        let Some(&first) = bytes.first() else {
+            return Err(self.ran_out(Some(1)));
+        };
+
+        let len = match first {
+            0x00..=0x7f => 1,
+            0xc2..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf4 => 4,
+            _ => return Err(PartialError::Rejected),
+        };
+
+        match bytes.get(..len) {
+            Some(encoded) => str::from_utf8(encoded)
+                .ok()
+                .and_then(|s| s.chars().next())
+                .map(|c| (c, len))
+                .ok_or(PartialError::Rejected),
+            None => match str::from_utf8(bytes) {
+                // a valid start of a character, that continues in the next chunk
+                Err(e) if e.error_len().is_none() => {
+                    Err(self.ran_out(Some(len.saturating_sub(bytes.len()))))
+                }
+                _ => Err(PartialError::Rejected),
+            },
+        }
+    }
+
+    /// returns the next character to be accepted
+    pub fn upcoming_char(&self) -> Result<char, PartialError> {
+        self.next_char(self.inner.leftover()).map(|(c, _)| c)
+    }
+
+    /// Accepts a character if the passed closure evaluates to true.
+    pub fn accept_char_with(&mut self, f: impl Fn(char) -> bool) -> Result<&'a str, PartialError> {
+        let (c, len) = self.next_char(self.inner.leftover())?;
+        if f(c) {
+            str::from_utf8(self.advance(len)).map_err(|_| PartialError::Rejected)
+        } else {
+            Err(PartialError::Rejected)
+        }
+    }
+
+    /// accepts a single char from the input.
+    pub fn accept_char(&mut self, c: char) -> Result<&'a str, PartialError> {
+        self.accept_char_with(|x| x == c)
+    }
+
+    /// Accepts a sequence of string-like values from the input.
+    ///
+    /// When the input ends before it, but what's there matches so far, more input is needed.
+    pub fn accept(&mut self, s: impl AsRef<str>) -> Result<&'a str, PartialError> {
+        self.accept_bytes(s.as_ref().as_bytes())
+            .and_then(|x| str::from_utf8(x).map_err(|_| PartialError::Rejected))
+    }
+
+    /// Accepts until the closure matches the current character.
+    ///
+    /// Returns what's accepted until then, but not including the matching character. When no
+    /// character matches before the input ends, more input is needed, since the next chunk might
+    /// still continue what would be accepted. Input that is not valid utf8 is rejected.
+    pub fn accept_until_char_with(
+        &mut self,
+        f: impl Fn(char) -> bool,
+    ) -> Result<&'a str, PartialError> {
+        let leftover = self.inner.leftover();
+        let mut len = 0;
+
+        loop {
+            match self.next_char(leftover.get(len..).unwrap_or_default()) {
+                Ok((c, _)) if f(c) => break,
+                Ok((_, char_len)) => len = len.saturating_add(char_len),
+                // the end of complete input
+                Err(PartialError::Rejected) if self.complete && len == leftover.len() => break,
+                Err(PartialError::Incomplete { .. }) => {
+                    return Err(PartialError::Incomplete { needed: None })
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        str::from_utf8(self.advance(len)).map_err(|_| PartialError::Rejected)
+    }
+
+    /// Accepts until a specific character is encountered
+    ///
+    /// Returns what's accepted until then, but not including the matching character.
+    pub fn accept_until_char(&mut self, c: char) -> Result<&'a str, PartialError> {
+        self.accept_until_char_with(|x| x == c)
+    }
+
+    /// Accepts until whitespace is encountered
+    ///
+    /// Returns what's accepted until then, but not including the whitespace
+    pub fn accept_until_whitespace(&mut self) -> Result<&'a str, PartialError> {
+        self.accept_until_char_with(|x| x.is_whitespace())
+    }
+
+    /// Accepts a single whitespace character.
+    pub fn accept_whitespace(&mut self) -> Result<&'a str, PartialError> {
+        self.accept_char_with(|x| x.is_whitespace())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Char, PartialError, PartialParseHelper};
+
+    // header = name ':' ' '* value "\r\n"
+    fn header<'a>(
+        ph: &mut PartialParseHelper<'a, [u8], Char>,
+    ) -> Result<(&'a str, &'a str), PartialError> {
+        ph.attempt(|ph| {
+            let name = ph.accept_until_char(':')?;
+            ph.accept_char(':')?;
+            while ph.accept_whitespace().is_ok() {}
+            let value = ph.accept_until_char('\r')?;
+            ph.accept("\r\n")?;
+            Ok((name, value))
+        })
+    }
+
+    #[test]
+    fn every_split() {
+        let input = "Host: example.com\r\nNaïve:  ✓ 🦀\r\n".as_bytes();
+
+        let mut expected = Vec::new();
+        let mut ph = PartialParseHelper::new_char_oriented(input);
+        ph.end_of_input();
+        while let Ok(h) = header(&mut ph) {
+            expected.push(h);
+        }
+        assert!(ph.done());
+        assert_eq!(expected.len(), 2);
+
+        // feed the input in two chunks, split at every possible point
+        for split in 0..=input.len() {
+            let mut parsed = Vec::new();
+
+            let mut ph = PartialParseHelper::new_char_oriented(&input[..split]);
+            let mut start = ph.mark();
+            loop {
+                match header(&mut ph) {
+                    Ok(h) => {
+                        parsed.push(h);
+                        start = ph.mark();
+                    }
+                    Err(PartialError::Incomplete { needed }) => {
+                        if let Some(needed) = needed {
+                            assert!(ph.bytes_accepted() + needed <= input.len());
+                        }
+                        break;
+                    }
+                    Err(PartialError::Rejected) => panic!("rejected at split {split}"),
+                }
+            }
+
+            let mut ph = PartialParseHelper::resume(input, start).unwrap();
+            ph.end_of_input();
+            while let Ok(h) = header(&mut ph) {
+                parsed.push(h);
+            }
+
+            assert!(ph.done(), "not done at split {split}");
+            assert_eq!(parsed, expected, "split at {split}");
+        }
+    }
+
+    #[test]
+    fn rejected_early() {
+        let mut ph = PartialParseHelper::new_byte_oriented(b"GEX".as_slice());
+        assert_eq!(ph.accept("GET /"), Err(PartialError::Rejected));
+        assert_eq!(ph.accept("GE"), Ok(b"GE".as_slice()));
+        assert_eq!(ph.accept_byte(b'T'), Err(PartialError::Rejected));
+        assert_eq!(
+            ph.accept_n_bytes(3),
+            Err(PartialError::Incomplete { needed: Some(2) })
+        );
+
+        let ph = PartialParseHelper::new_char_oriented(b"\xff".as_slice());
+        assert_eq!(ph.upcoming_char(), Err(PartialError::Rejected));
+
+        let mut ph = PartialParseHelper::new_char_oriented("é".as_bytes().get(..1).unwrap());
+        assert_eq!(
+            ph.upcoming_char(),
+            Err(PartialError::Incomplete { needed: Some(1) })
+        );
+        ph.end_of_input();
+        assert_eq!(ph.accept_until_whitespace(), Err(PartialError::Rejected));
+    }
+}
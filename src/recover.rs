@@ -0,0 +1,325 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::Mark;
+use crate::{Byte, Char, ParseHelper, Span};
+
+impl<'a, T: ?Sized> ParseHelper<'a, T, Char>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    /// Skips characters until `sync` returns `true`, or until the end of the input.
+    ///
+    /// `sync` is called at every position on a backup of the parse helper, so whatever it
+    /// accepts is not skipped: the parse helper stops right in front of the synchronisation point.
+    /// That includes the end of the input, where `sync` is called one last time.
+    /// Returns the [`Span`] of everything that was skipped.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("let = 3; let y = 4;");
+    ///
+    /// let skipped = ph.recover_until(|ph| ph.accept_char(';').is_some());
+    /// assert_eq!(ph.slice(skipped), "let = 3");
+    /// assert_eq!(ph.leftover(), "; let y = 4;");
+    /// ```
+    pub fn recover_until(&mut self, mut sync: impl FnMut(&mut Self) -> bool) -> Span<Char> {
+        let start = self.mark();
+
+        while !sync(&mut self.create_backup()) {
+            let Some(c) = self.upcoming_char() else {
+                break;
+            };
+            self.advance(c.len_utf8());
+        }
+
+        Span::new(start, self.mark())
+    }
+
+    /// Skips a bracketed group, starting at `open` and ending after the matching `close`.
+    /// Nested groups are skipped as a whole.
+    ///
+    /// Returns `None` without skipping anything when the next character is not `open`.
+    /// When the group is never closed, everything until the end of the input is skipped.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("{ a { b } c } d");
+    ///
+    /// let skipped = ph.recover_balanced('{', '}').unwrap();
+    /// assert_eq!(ph.slice(skipped), "{ a { b } c }");
+    /// assert_eq!(ph.leftover(), " d");
+    ///
+    /// assert_eq!(ph.recover_balanced('{', '}'), None);
+    /// ```
+    pub fn recover_balanced(&mut self, open: char, close: char) -> Option<Span<Char>> {
+        let start = self.mark();
+        self.accept_char(open)?;
+
+        let mut depth = 1usize;
+        while let Some(c) = self.upcoming_char() {
+            self.advance(c.len_utf8());
+
+            if c == close {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    break;
+                }
+            } else if c == open {
+                depth = depth.saturating_add(1);
+            }
+        }
+
+        Some(Span::new(start, self.mark()))
+    }
+}
+
+impl<'a, T: ?Sized> ParseHelper<'a, T, Byte>
+where
+    T: AsRef<[u8]>,
+{
+    /// Skips bytes until `sync` returns `true`, or until the end of the input.
+    ///
+    /// `sync` is called at every position on a backup of the parse helper, so whatever it
+    /// accepts is not skipped: the parse helper stops right in front of the synchronisation point.
+    /// That includes the end of the input, where `sync` is called one last time.
+    /// Returns the [`Span`] of everything that was skipped.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented("x = ; y = 4;");
+    ///
+    /// let skipped = ph.recover_until(|ph| ph.accept_byte(b';'));
+    /// assert_eq!(ph.slice(skipped), "x = ");
+    /// assert_eq!(ph.leftover(), b"; y = 4;");
+    /// ```
+    pub fn recover_until(&mut self, mut sync: impl FnMut(&mut Self) -> bool) -> Span<Byte> {
+        let start = self.mark();
+
+        while !sync(&mut self.create_backup()) {
+            if self.done() {
+                break;
+            }
+            self.advance(1);
+        }
+
+        Span::new(start, self.mark())
+    }
+
+    /// Skips a bracketed group, starting at `open` and ending after the matching `close`.
+    /// Nested groups are skipped as a whole.
+    ///
+    /// Returns `None` without skipping anything when the next byte is not `open`.
+    /// When the group is never closed, everything until the end of the input is skipped.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented("(a (b) c");
+    ///
+    /// let skipped = ph.recover_balanced(b'(', b')').unwrap();
+    /// assert_eq!(ph.slice(skipped), "(a (b) c");
+    /// assert!(ph.done());
+    /// ```
+    pub fn recover_balanced(&mut self, open: u8, close: u8) -> Option<Span<Byte>> {
+        let start = self.mark();
+        if !self.accept_byte(open) {
+            return None;
+        }
+
+        let mut depth = 1usize;
+        while let Some(b) = self.upcoming_byte() {
+            self.advance(1);
+
+            if b == close {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    break;
+                }
+            } else if b == open {
+                depth = depth.saturating_add(1);
+            }
+        }
+
+        Some(Span::new(start, self.mark()))
+    }
+}
+
+/// Collects errors while parsing continues, so more than just the first error can be reported.
+///
+/// Every error is stored together with the [`Mark`] it happened at. Regions of the input that
+/// were skipped to recover from an error can be recorded as well, for example to grey them out
+/// in an editor.
+///
+/// ```
+/// use parse_helper::{ErrorSink, ParseHelper};
+///
+/// let mut ph = ParseHelper::new_char_oriented("1;x;3;");
+/// let mut sink = ErrorSink::new();
+/// let mut numbers = Vec::new();
+///
+/// while !ph.done() {
+///     let start = ph.mark();
+///     match ph.accept_until_char(';').parse::<u32>() {
+///         Ok(n) => numbers.push(n),
+///         Err(e) => sink.report(start, e),
+///     }
+///     ph.accept_char(';');
+/// }
+///
+/// assert_eq!(numbers, [1, 3]);
+/// assert_eq!(sink.errors().len(), 1);
+/// assert_eq!(sink.errors()[0].0.byte_position(), 2);
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ErrorSink<E, B> {
+    errors: Vec<(Mark<B>, E)>,
+    recovered: Vec<Span<B>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<E, B> ErrorSink<E, B> {
+    /// Creates an empty error sink.
+    pub fn new() -> Self {
+        Self {
+            errors: Vec::new(),
+            recovered: Vec::new(),
+        }
+    }
+
+    /// Adds an error that happened at `mark`.
+    pub fn report(&mut self, mark: Mark<B>, error: E) {
+        self.errors.push((mark, error));
+    }
+
+    /// Records a region of the input that was skipped to recover from an error,
+    /// as returned by [`ParseHelper::recover_until`] or [`ParseHelper::recover_balanced`].
+    pub fn recovered(&mut self, span: Span<B>) {
+        self.recovered.push(span);
+    }
+
+    /// Returns `true` if any errors were reported.
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// All errors reported sofar, in the order they were reported.
+    pub fn errors(&self) -> &[(Mark<B>, E)] {
+        &self.errors
+    }
+
+    /// All regions recorded with [`recovered`](Self::recovered).
+    pub fn recovered_spans(&self) -> &[Span<B>] {
+        &self.recovered
+    }
+
+    /// Returns the reported errors, or `Ok(value)` if there were none.
+    pub fn finish<V>(self, value: V) -> Result<V, Vec<(Mark<B>, E)>> {
+        if self.errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<E, B> Default for ErrorSink<E, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ParseHelper;
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn recover_statements() {
+        let mut ph = ParseHelper::new_char_oriented("a = 1; b = ; { c = ( } ; d = 4;");
+        let mut sink = crate::ErrorSink::new();
+        let mut parsed = Vec::new();
+
+        while !ph.done() {
+            ph.accept_zero_or_more_whitespace();
+            let start = ph.mark();
+
+            if let Some(span) = ph.recover_balanced('{', '}') {
+                sink.report(start, "unexpected block");
+                sink.recovered(span);
+            } else {
+                let statement = ph.accept_until_char(';');
+                match statement.split_once(" = ") {
+                    Some((name, value)) if !value.is_empty() => parsed.push(name),
+                    _ => sink.report(start, "invalid statement"),
+                }
+            }
+
+            let skipped = ph.recover_until(|ph| ph.accept_char(';').is_some());
+            if !skipped.is_empty() {
+                sink.recovered(skipped);
+            }
+            ph.accept_char(';');
+        }
+
+        assert_eq!(parsed, ["a", "d"]);
+        assert_eq!(
+            sink.errors()
+                .iter()
+                .map(|(mark, e)| (mark.byte_position(), *e))
+                .collect::<Vec<_>>(),
+            [(7, "invalid statement"), (13, "unexpected block")]
+        );
+        assert_eq!(
+            sink.recovered_spans()
+                .iter()
+                .map(|span| ph.slice(*span))
+                .collect::<Vec<_>>(),
+            ["{ c = ( }", " "]
+        );
+        assert!(sink.finish(()).is_err());
+    }
+
+    #[test]
+    fn recover_until_end() {
+        let mut ph = ParseHelper::new_char_oriented("日本");
+        let skipped = ph.recover_until(|_| false);
+        assert_eq!(ph.slice(skipped), "日本");
+        assert!(ph.done());
+        assert!(ph.recover_until(|_| false).is_empty());
+    }
+
+    #[test]
+    fn recover_until_calls_sync_at_end() {
+        let mut calls = 0;
+        let mut ph = ParseHelper::new_char_oriented("ab");
+        ph.recover_until(|_| {
+            calls += 1;
+            false
+        });
+        assert_eq!(calls, 3);
+
+        let mut ph = ParseHelper::new_char_oriented("ab");
+        let skipped = ph.recover_until(|ph| ph.done());
+        assert_eq!(ph.slice(skipped), "ab");
+
+        let mut calls = 0;
+        let mut ph = ParseHelper::new_byte_oriented("ab");
+        ph.recover_until(|_| {
+            calls += 1;
+            false
+        });
+        assert_eq!(calls, 3);
+
+        let mut ph = ParseHelper::new_byte_oriented("ab");
+        let skipped = ph.recover_until(|ph| ph.done());
+        assert_eq!(ph.slice(skipped), "ab");
+        assert!(ph.done());
+    }
+}
@@ -0,0 +1,374 @@
+use core::ops::{Deref, Range};
+
+use crate::{any::private::SliceRange, Mark, ParseHelper};
+
+/// A range of the input between two [`Mark`]s, which includes the start but excludes the end.
+///
+/// Spans can be used anywhere a `Range<Mark<B>>` can be used, like in
+/// [`ParseHelper::slice`]. Because the end is exclusive, that's also true for
+/// [`Char`](crate::Char) oriented parse helpers.
+///
+/// ```
+/// use parse_helper::ParseHelper;
+///
+/// let mut ph = ParseHelper::new_char_oriented("ab cd");
+///
+/// let ((), ab) = ph.span_of(|ph| { ph.accept_until_whitespace(); });
+/// ph.accept_whitespace();
+/// let ((), cd) = ph.span_of(|ph| { ph.accept_until_whitespace(); });
+///
+/// assert_eq!(ph.slice(ab), "ab");
+/// assert_eq!(ph.slice(cd), "cd");
+/// assert_eq!(ph.slice(ab.join(cd)), "ab cd");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "", try_from = "SpanBounds<B>")
+)]
+pub struct Span<B> {
+    start: Mark<B>,
+    end: Mark<B>,
+}
+
+// what a span deserializes from, before checking that the start doesn't lie after the end
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(bound = "")]
+struct SpanBounds<B> {
+    start: Mark<B>,
+    end: Mark<B>,
+}
+
+#[cfg(feature = "serde")]
+impl<B> TryFrom<SpanBounds<B>> for Span<B> {
+    type Error = &'static str;
+
+    fn try_from(SpanBounds { start, end }: SpanBounds<B>) -> Result<Self, Self::Error> {
+        if start.byte_position() <= end.byte_position() {
+            Ok(Self { start, end })
+        } else {
+            Err("the start of a span lies after its end")
+        }
+    }
+}
+
+impl<B> Span<B> {
+    /// Creates a span between two marks. The marks may be given in any order.
+    pub fn new(a: Mark<B>, b: Mark<B>) -> Self {
+        if a.byte_position() <= b.byte_position() {
+            Self { start: a, end: b }
+        } else {
+            Self { start: b, end: a }
+        }
+    }
+}
+
+impl<B: Copy> Span<B> {
+    /// Creates an empty span at a mark.
+    pub fn empty(at: Mark<B>) -> Self {
+        Self { start: at, end: at }
+    }
+
+    /// The mark at the start of the span, which is part of the span.
+    pub fn start(&self) -> Mark<B> {
+        self.start
+    }
+
+    /// The mark at the end of the span, which is not part of the span anymore.
+    pub fn end(&self) -> Mark<B> {
+        self.end
+    }
+
+    /// The length of the span in bytes.
+    pub fn len(&self) -> usize {
+        self.end
+            .byte_position()
+            .saturating_sub(self.start.byte_position())
+    }
+
+    /// Returns `true` if the span has a length of zero.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the mark lies within the span.
+    ///
+    /// Since the end of a span is exclusive, an empty span contains no marks at all.
+    pub fn contains(&self, mark: Mark<B>) -> bool {
+        (self.start.byte_position()..self.end.byte_position()).contains(&mark.byte_position())
+    }
+
+    /// Returns `true` if `other` lies completely within this span.
+    pub fn contains_span(&self, other: Span<B>) -> bool {
+        self.start.byte_position() <= other.start.byte_position()
+            && other.end.byte_position() <= self.end.byte_position()
+    }
+
+    /// The smallest span that covers both spans, including anything in between them.
+    pub fn join(self, other: Span<B>) -> Self {
+        let start = if self.start.byte_position() <= other.start.byte_position() {
+            self.start
+        } else {
+            other.start
+        };
+        let end = if self.end.byte_position() >= other.end.byte_position() {
+            self.end
+        } else {
+            other.end
+        };
+
+        Self { start, end }
+    }
+
+    /// The part that both spans have in common.
+    ///
+    /// Returns `None` if the spans don't overlap. Spans that only touch have an empty intersection.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("abcd");
+    ///
+    /// let ((), ab) = ph.span_of(|ph| { ph.accept("ab"); });
+    /// let ((), cd) = ph.span_of(|ph| { ph.accept("cd"); });
+    ///
+    /// assert!(ab.intersect(cd).unwrap().is_empty());
+    /// assert_eq!(ab.join(cd).intersect(ab), Some(ab));
+    /// assert_eq!(ph.slice(ab.join(cd)), "abcd");
+    /// ```
+    pub fn intersect(self, other: Span<B>) -> Option<Self> {
+        let start = if self.start.byte_position() >= other.start.byte_position() {
+            self.start
+        } else {
+            other.start
+        };
+        let end = if self.end.byte_position() <= other.end.byte_position() {
+            self.end
+        } else {
+            other.end
+        };
+
+        (start.byte_position() <= end.byte_position()).then_some(Self { start, end })
+    }
+
+    /// Converts the span to a range of marks.
+    pub fn range(&self) -> Range<Mark<B>> {
+        self.start..self.end
+    }
+}
+
+impl<B> From<Range<Mark<B>>> for Span<B> {
+    fn from(value: Range<Mark<B>>) -> Self {
+        Self::new(value.start, value.end)
+    }
+}
+
+impl<B: Copy> From<Mark<B>> for Span<B> {
+    fn from(value: Mark<B>) -> Self {
+        Self::empty(value)
+    }
+}
+
+impl<B: Copy> From<Span<B>> for Range<Mark<B>> {
+    fn from(value: Span<B>) -> Self {
+        value.range()
+    }
+}
+
+impl<'a, B, T: ?Sized> SliceRange<'a, B, T> for Span<B> {
+    type RangeTy = Range<usize>;
+
+    fn range(&self) -> Self::RangeTy {
+        self.start.byte_position()..self.end.byte_position()
+    }
+}
+
+/// A value together with the [`Span`] of the input it was parsed from.
+///
+/// Created by [`ParseHelper::spanned`], and useful to tag AST nodes with their location.
+/// Derefs to the value.
+///
+/// With the `serde` feature enabled, this (de)serializes as a struct with a `value` and a `span`
+/// field, where the span has a `start` and `end` byte position.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "T: serde::Serialize",
+        deserialize = "T: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Spanned<T, B> {
+    /// The parsed value
+    pub value: T,
+    /// Where in the input the value was parsed from
+    pub span: Span<B>,
+}
+
+impl<T, B> Spanned<T, B> {
+    /// Wraps a value together with its span.
+    pub fn new(value: T, span: Span<B>) -> Self {
+        Self { value, span }
+    }
+
+    /// Applies a function to the value, keeping the span.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Spanned<U, B> {
+        Spanned {
+            value: f(self.value),
+            span: self.span,
+        }
+    }
+
+    /// Returns the value, discarding the span.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, B> Deref for Spanned<T, B> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<'a, T: ?Sized, B> ParseHelper<'a, T, B> {
+    /// Like [`slice_accepted`](Self::slice_accepted), but returns the [`Span`] of everything
+    /// that was accepted in the closure instead of a slice of the input.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("ab cd");
+    ///
+    /// let (_, span) = ph.span_of(|ph| {
+    ///     ph.accept_until_whitespace();
+    ///     ph.accept_one_or_more_whitespace();
+    /// });
+    ///
+    /// assert_eq!(span.start().byte_position(), 0);
+    /// assert_eq!(span.len(), 3);
+    /// assert_eq!(ph.slice(span), "ab ");
+    /// ```
+    pub fn span_of<P>(&mut self, closure: impl FnOnce(&mut Self) -> P) -> (P, Span<B>) {
+        let start = self.mark();
+        let res = closure(self);
+        let end = self.mark();
+
+        (res, Span::new(start, end))
+    }
+
+    /// Runs a parsing closure and records the [`Span`] of everything it accepted together with
+    /// the value it returned.
+    ///
+    /// Like [`slice_accepted_option`](Self::slice_accepted_option), the parse helper resets to
+    /// where it was before if the closure returns `None`.
+    ///
+    /// ```
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("12 ab");
+    ///
+    /// let number = ph.spanned(|ph| ph.accept_until_whitespace().parse::<u32>().ok()).unwrap();
+    /// assert_eq!(*number, 12);
+    /// assert_eq!(ph.slice(number.span), "12");
+    ///
+    /// ph.accept_whitespace();
+    /// assert_eq!(ph.spanned(|ph| ph.accept_until_whitespace().parse::<u32>().ok()), None);
+    /// assert_eq!(ph.leftover(), "ab");
+    /// ```
+    pub fn spanned<P>(
+        &mut self,
+        closure: impl FnOnce(&mut Self) -> Option<P>,
+    ) -> Option<Spanned<P, B>> {
+        let old = self.create_backup();
+        let (res, span) = self.span_of(closure);
+
+        match res {
+            Some(value) => Some(Spanned { value, span }),
+            None => {
+                self.restore_backup(old);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Mark, ParseHelper, Span};
+
+    #[test]
+    fn span_operations() {
+        let mut ph = ParseHelper::new_byte_oriented("abcdef");
+        let a = ph.mark();
+        ph.skip_bytes(2);
+        let c = ph.mark();
+        ph.skip_bytes(2);
+        let e = ph.mark();
+        ph.skip_bytes(2);
+        let end = ph.mark();
+
+        let ac = Span::new(c, a);
+        assert_eq!(ac.start(), a);
+        assert_eq!(ac.end(), c);
+        assert_eq!(ac.len(), 2);
+        assert!(!ac.is_empty());
+        assert!(ac.contains(a));
+        assert!(!ac.contains(c));
+        assert!(Span::empty(c).is_empty());
+        assert!(!Span::empty(c).contains(c));
+
+        let ce = Span::from(c..e);
+        let ae = ac.join(ce);
+        assert_eq!(ae, Span::new(a, e));
+        assert!(ae.contains_span(ac));
+        assert!(!ac.contains_span(ae));
+        assert_eq!(ae.intersect(Span::new(c, end)), Some(ce));
+        assert_eq!(ac.intersect(ce), Some(Span::empty(c)));
+        assert_eq!(ac.intersect(Span::new(e, end)), None);
+
+        assert_eq!(ph.slice(ae), "abcd");
+        assert_eq!(ph.try_slice(ce), Ok(b"cd".as_slice()));
+        let r: core::ops::Range<Mark<_>> = ce.into();
+        assert_eq!(ph.slice(r), "cd");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serialize_spanned() {
+        use crate::{Char, Spanned};
+
+        let mut ph = ParseHelper::new_char_oriented("ab cd");
+        ph.accept_until_whitespace();
+        ph.accept_whitespace();
+        let word = ph.spanned(|ph| Some(ph.accept_until_whitespace())).unwrap();
+
+        let json = serde_json::to_string(&word).unwrap();
+        assert_eq!(json, r#"{"value":"cd","span":{"start":3,"end":5}}"#);
+
+        let back: Spanned<&str, Char> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, word);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn deserialize_reversed_span() {
+        use crate::{Char, Span, Spanned};
+
+        let err = serde_json::from_str::<Span<Char>>(r#"{"start":5,"end":3}"#).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("the start of a span lies after its end"));
+
+        let json = r#"{"value":"cd","span":{"start":5,"end":3}}"#;
+        assert!(serde_json::from_str::<Spanned<&str, Char>>(json).is_err());
+
+        let span: Span<Char> = serde_json::from_str(r#"{"start":3,"end":3}"#).unwrap();
+        assert!(span.is_empty());
+    }
+}
@@ -0,0 +1,730 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt::{self, Debug, Formatter},
+    hash::{Hash, Hasher},
+    io::{self, Read},
+    marker::PhantomData,
+    ops::Range,
+    rc::Rc,
+    str,
+};
+
+use crate::{Byte, Char, Mark};
+
+/// A parse helper that reads its input from an [`io::Read`] as it goes, instead of borrowing
+/// input that is fully in memory.
+///
+/// It offers the same `accept*`, `accept_until*` and `upcoming*` methods as a
+/// [`ParseHelper`](crate::ParseHelper). Input is read into an internal buffer when it's needed,
+/// and input that can't be returned to anymore is discarded from it. That is everything before
+/// the current position and before the oldest [backup](Self::create_backup) that is still
+/// alive. Accepted slices borrow this buffer, so they must be dropped before parsing continues.
+///
+/// When reading fails, the error is stored (see [`io_error`](Self::io_error)) and the input is
+/// treated as if it ended there.
+///
+/// ```
+/// use parse_helper::StreamingParseHelper;
+///
+/// let input: &[u8] = b"GET /index.html HTTP/1.1\r\n";
+/// let mut ph = StreamingParseHelper::new_char_oriented(input);
+///
+/// assert_eq!(ph.accept_until_whitespace(), "GET");
+/// ph.accept_whitespace();
+/// assert_eq!(ph.accept_until_whitespace(), "/index.html");
+/// ph.accept_whitespace();
+///
+/// let backup = ph.create_backup();
+/// assert_eq!(ph.accept("HTTP/2"), None);
+/// assert_eq!(ph.accept("HTTP/1."), Some("HTTP/1."));
+/// ph.restore_backup(backup);
+///
+/// assert_eq!(ph.accept_until_char('\r'), "HTTP/1.1");
+/// assert_eq!(ph.accept("\r\n"), Some("\r\n"));
+/// assert!(ph.done());
+/// ```
+pub struct StreamingParseHelper<R, B> {
+    reader: R,
+    buffer: Vec<u8>,
+    // the position in the input of the first byte in the buffer
+    buffer_start: usize,
+    byte_position: usize,
+    // positions that have to stay in the buffer, with how often they are pinned.
+    // Shared with the backups, so they can unpin their position when they are dropped.
+    pinned: Rc<RefCell<BTreeMap<usize, usize>>>,
+    chunk_size: usize,
+    eof: bool,
+    error: Option<io::Error>,
+    boundary_assumption: PhantomData<B>,
+}
+
+/// A position a [`StreamingParseHelper`] can return to, created by
+/// [`create_backup`](StreamingParseHelper::create_backup).
+///
+/// As long as the backup is not [restored](StreamingParseHelper::restore_backup),
+/// [committed](StreamingParseHelper::commit) or dropped, the input after it is kept in memory.
+#[must_use = "the input after a backup is kept in memory until it is restored, committed or dropped"]
+pub struct StreamBackup<B> {
+    byte_position: usize,
+    pinned: Rc<RefCell<BTreeMap<usize, usize>>>,
+    boundary_assumption: PhantomData<B>,
+}
+
+impl<B> StreamBackup<B> {
+    /// The mark at the position of this backup.
+    ///
+    /// Unlike [`StreamingParseHelper::mark`], the input after it stays in the buffer for as long
+    /// as the backup is alive, so it can be used to [`slice`](StreamingParseHelper::slice).
+    pub fn mark(&self) -> Mark<B> {
+        Mark::new(self.byte_position)
+    }
+}
+
+impl<B> Drop for StreamBackup<B> {
+    fn drop(&mut self) {
+        unpin(&self.pinned, self.byte_position);
+    }
+}
+
+impl<B> PartialEq for StreamBackup<B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.byte_position == other.byte_position
+    }
+}
+
+impl<B> Eq for StreamBackup<B> {}
+
+impl<B> Hash for StreamBackup<B> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.byte_position.hash(state);
+    }
+}
+
+impl<B> Debug for StreamBackup<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamBackup")
+            .field("byte_position", &self.byte_position)
+            .finish_non_exhaustive()
+    }
+}
+
+fn pin(pinned: &RefCell<BTreeMap<usize, usize>>, position: usize) {
+    let mut pinned = pinned.borrow_mut();
+    let count = pinned.entry(position).or_default();
+    *count = count.saturating_add(1);
+}
+
+fn unpin(pinned: &RefCell<BTreeMap<usize, usize>>, position: usize) {
+    let mut pinned = pinned.borrow_mut();
+    if let Some(count) = pinned.get_mut(&position) {
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            pinned.remove(&position);
+        }
+    }
+}
+
+impl<R: Read> StreamingParseHelper<R, Byte> {
+    /// Creates a new [`StreamingParseHelper`] that assumes
+    /// steps can be taken one byte at a time.
+    pub fn new_byte_oriented(reader: R) -> Self {
+        Self::new(reader)
+    }
+}
+
+impl<R: Read> StreamingParseHelper<R, Char> {
+    /// Creates a new [`StreamingParseHelper`] that assumes
+    /// steps can only be taken one utf8 codepoint at a time.
+    ///
+    /// Input that is not valid utf8 is never accepted by methods that accept characters.
+    pub fn new_char_oriented(reader: R) -> Self {
+        Self::new(reader)
+    }
+}
+
+impl<R: Read, B> StreamingParseHelper<R, B> {
+    /// How many bytes are read at once by default.
+    pub const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
+
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            buffer_start: 0,
+            byte_position: 0,
+            pinned: Rc::default(),
+            chunk_size: Self::DEFAULT_CHUNK_SIZE,
+            eof: false,
+            error: None,
+            boundary_assumption: PhantomData,
+        }
+    }
+
+    /// Sets how many bytes are read from the reader at once. Defaults to
+    /// [`DEFAULT_CHUNK_SIZE`](Self::DEFAULT_CHUNK_SIZE).
+    pub fn set_chunk_size(&mut self, chunk_size: usize) {
+        self.chunk_size = chunk_size.max(1);
+    }
+
+    /// Creates a backup of the current position, to return to with
+    /// [`restore_backup`](Self::restore_backup).
+    ///
+    /// Until the backup is restored, [committed](Self::commit) or dropped, everything after it
+    /// stays in the buffer.
+    pub fn create_backup(&mut self) -> StreamBackup<B> {
+        pin(&self.pinned, self.byte_position);
+        StreamBackup {
+            byte_position: self.byte_position,
+            pinned: Rc::clone(&self.pinned),
+            boundary_assumption: PhantomData,
+        }
+    }
+
+    /// Returns to the position of a backup.
+    ///
+    /// Restoring a backup of another streaming parse helper is a logic error. It will not panic,
+    /// but the position it ends up at is unspecified.
+    pub fn restore_backup(&mut self, backup: StreamBackup<B>) {
+        self.byte_position = backup
+            .byte_position
+            .clamp(self.buffer_start, self.buffer_end());
+    }
+
+    /// Forgets a backup without returning to it, so the input before the current position
+    /// can be discarded from the buffer. This is the same as dropping the backup.
+    pub fn commit(&mut self, backup: StreamBackup<B>) {
+        drop(backup);
+    }
+
+    /// Creates a mark at the current position.
+    ///
+    /// Marks don't keep input in the buffer, so to [`slice`](Self::slice) between marks, take the
+    /// start from a backup with [`StreamBackup::mark`].
+    pub fn mark(&self) -> Mark<B> {
+        Mark::new(self.byte_position)
+    }
+
+    /// Returns how many bytes have been accepted sofar.
+    pub fn bytes_accepted(&self) -> usize {
+        self.byte_position
+    }
+
+    /// Returns how many bytes are currently kept in the buffer.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns `true` if the end of the input has been reached.
+    pub fn done(&mut self) -> bool {
+        !self.fill(1)
+    }
+
+    /// returns the next byte that is going to be parsed.
+    pub fn upcoming_byte(&mut self) -> Option<u8> {
+        self.fill(1);
+        self.available().first().copied()
+    }
+
+    /// The error reading failed with, if it did. The input is treated as if it ended there.
+    pub fn io_error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    /// Takes the error reading failed with out of the parse helper, to try reading again.
+    pub fn take_io_error(&mut self) -> Option<io::Error> {
+        let error = self.error.take();
+        if error.is_some() {
+            self.eof = false;
+        }
+        error
+    }
+
+    /// Returns the reader, discarding any input that was read but not yet accepted.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn buffer_end(&self) -> usize {
+        self.buffer_start.saturating_add(self.buffer.len())
+    }
+
+    // moves forward over `n` bytes that are in the buffer
+    fn advance(&mut self, n: usize) {
+        self.byte_position = self.byte_position.saturating_add(n);
+    }
+
+    // what's in the buffer after the current position
+    fn available(&self) -> &[u8] {
+        self.buffer
+            .get(self.byte_position.saturating_sub(self.buffer_start)..)
+            .unwrap_or_default()
+    }
+
+    // the buffered input between two positions
+    fn buffered_slice(&self, start: usize, end: usize) -> Option<&[u8]> {
+        self.buffer
+            .get(start.checked_sub(self.buffer_start)?..end.checked_sub(self.buffer_start)?)
+    }
+
+    // reads until at least `n` bytes are available after the current position.
+    // Returns `false` if the input ended before that.
+    fn fill(&mut self, n: usize) -> bool {
+        while self.available().len() < n {
+            if self.eof {
+                return false;
+            }
+            self.read_chunk();
+        }
+        true
+    }
+
+    fn read_chunk(&mut self) {
+        self.discard();
+
+        let len = self.buffer.len();
+        self.buffer.resize(len.saturating_add(self.chunk_size), 0);
+
+        loop {
+            let res = self
+                .reader
+                .read(self.buffer.get_mut(len..).unwrap_or_default());
+
+            match res {
+                Ok(n) => {
+                    self.buffer.truncate(len.saturating_add(n));
+                    self.eof = n == 0;
+                    return;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.buffer.truncate(len);
+                    self.error = Some(e);
+                    self.eof = true;
+                    return;
+                }
+            }
+        }
+    }
+
+    // drops input from the buffer that can't be returned to anymore
+    fn discard(&mut self) {
+        let oldest_pinned = self.pinned.borrow().keys().next().copied();
+        let keep_from = match oldest_pinned {
+            Some(pinned) => pinned.min(self.byte_position),
+            None => self.byte_position,
+        };
+        let discardable = keep_from.saturating_sub(self.buffer_start);
+
+        // only move the buffer around when that frees up a good part of it
+        if discardable > 0 && discardable >= self.buffer.len() / 2 {
+            self.buffer.drain(..discardable);
+            self.buffer_start = keep_from;
+        }
+    }
+
+    // runs a closure while the current position is pinned, and returns where it started
+    fn pinned_while<P>(&mut self, f: impl FnOnce(&mut Self) -> P) -> (P, usize) {
+        let start = self.byte_position;
+        pin(&self.pinned, start);
+        let res = f(self);
+        unpin(&self.pinned, start);
+
+        (res, start)
+    }
+}
+
+impl<R: Read> StreamingParseHelper<R, Byte> {
+    /// Returns the input between two marks.
+    ///
+    /// Returns `None` when part of that input was already discarded from the buffer, or when
+    /// it wasn't read yet.
+    pub fn slice(&self, range: Range<Mark<Byte>>) -> Option<&[u8]> {
+        self.buffered_slice(range.start.byte_position(), range.end.byte_position())
+    }
+
+    /// accepts a single byte from the input
+    pub fn accept_byte(&mut self, c: u8) -> bool {
+        self.accept_byte_with(|x| c == x).is_some()
+    }
+
+    /// Accepts a byte if the passed closure evaluates to true.
+    ///
+    /// Returns what it accepted, if anything
+    pub fn accept_byte_with(&mut self, f: impl Fn(u8) -> bool) -> Option<u8> {
+        let b = self.upcoming_byte()?;
+        if f(b) {
+            self.advance(1);
+            Some(b)
+        } else {
+            None
+        }
+    }
+
+    /// accepts a sequence of bytes-like values from the input
+    pub fn accept(&mut self, bytes: impl AsRef<[u8]>) -> Option<&[u8]> {
+        let bytes = bytes.as_ref();
+        self.fill(bytes.len());
+
+        if self.available().starts_with(bytes) {
+            let start = self.byte_position;
+            self.advance(bytes.len());
+            self.buffered_slice(start, self.byte_position)
+        } else {
+            None
+        }
+    }
+
+    /// Accepts exactly `n` bytes.
+    ///
+    /// Returns `None` without accepting anything when the input ends before that.
+    pub fn accept_n_bytes(&mut self, n: usize) -> Option<&[u8]> {
+        if !self.fill(n) {
+            return None;
+        }
+
+        let start = self.byte_position;
+        self.advance(n);
+        self.buffered_slice(start, self.byte_position)
+    }
+
+    /// Accepts until the closure matches the current byte.
+    ///
+    /// Returns what's accepted until then, but not including the matching byte.
+    pub fn accept_until_byte_with(&mut self, f: impl Fn(u8) -> bool) -> &[u8] {
+        let ((), start) = self.pinned_while(|ph| loop {
+            let available = ph.available();
+            match available.iter().position(|&b| f(b)) {
+                Some(idx) => {
+                    ph.advance(idx);
+                    break;
+                }
+                None => {
+                    ph.advance(available.len());
+                    if !ph.fill(1) {
+                        break;
+                    }
+                }
+            }
+        });
+
+        self.buffered_slice(start, self.byte_position)
+            .unwrap_or_default()
+    }
+
+    /// Accepts until a specific byte is encountered
+    ///
+    /// Returns what's accepted until then, but not including the matching byte.
+    pub fn accept_until_byte(&mut self, c: u8) -> &[u8] {
+        self.accept_until_byte_with(|x| x == c)
+    }
+
+    /// Like [`ParseHelper::slice_accepted`](crate::ParseHelper::slice_accepted). Everything
+    /// accepted in the closure is kept in the buffer until it returns.
+    pub fn slice_accepted<P>(&mut self, closure: impl FnOnce(&mut Self) -> P) -> (P, &[u8]) {
+        let (res, start) = self.pinned_while(closure);
+        (
+            res,
+            self.buffered_slice(start, self.byte_position)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Like [`ParseHelper::slice_accepted_option`](crate::ParseHelper::slice_accepted_option):
+    /// if the closure returns `None`, the parse helper resets to where it was before.
+    pub fn slice_accepted_option(
+        &mut self,
+        closure: impl FnOnce(&mut Self) -> Option<()>,
+    ) -> Option<&[u8]> {
+        let backup = self.create_backup();
+        let start = self.byte_position;
+
+        match closure(self) {
+            Some(()) => {
+                self.commit(backup);
+                self.buffered_slice(start, self.byte_position)
+            }
+            None => {
+                self.restore_backup(backup);
+                None
+            }
+        }
+    }
+}
+
+impl<R: Read> StreamingParseHelper<R, Char> {
+    /// Returns the input between two marks.
+    ///
+    /// Returns `None` when part of that input was already discarded from the buffer, or when
+    /// it wasn't read yet.
+    pub fn slice(&self, range: Range<Mark<Char>>) -> Option<&str> {
+        self.buffered_str(range.start.byte_position(), range.end.byte_position())
+    }
+
+    fn buffered_str(&self, start: usize, end: usize) -> Option<&str> {
+        str::from_utf8(self.buffered_slice(start, end)?).ok()
+    }
+
+    /// returns the next character to be accepted
+    ///
+    /// Returns `None` at the end of the input, and when the next bytes are not valid utf8.
+    pub fn upcoming_char(&mut self) -> Option<char> {
+        let len = match self.upcoming_byte()? {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return None,
+        };
+        self.fill(len);
+
+        str::from_utf8(self.available().get(..len)?)
+            .ok()?
+            .chars()
+            .next()
+    }
+
+    /// Accepts a character if the passed closure evaluates to true.
+    ///
+    /// Returns what it accepted, if anything.
+    pub fn accept_char_with(&mut self, f: impl Fn(char) -> bool) -> Option<&str> {
+        let c = self.upcoming_char()?;
+        if f(c) {
+            let start = self.byte_position;
+            self.advance(c.len_utf8());
+            self.buffered_str(start, self.byte_position)
+        } else {
+            None
+        }
+    }
+
+    /// accepts a single char from the input.
+    pub fn accept_char(&mut self, c: char) -> Option<&str> {
+        self.accept_char_with(|x| x == c)
+    }
+
+    /// Accepts a sequence of string-like values from the input.
+    pub fn accept(&mut self, s: impl AsRef<str>) -> Option<&str> {
+        let bytes = s.as_ref().as_bytes();
+        self.fill(bytes.len());
+
+        if self.available().starts_with(bytes) {
+            let start = self.byte_position;
+            self.advance(bytes.len());
+            self.buffered_str(start, self.byte_position)
+        } else {
+            None
+        }
+    }
+
+    /// Accepts until the closure matches the current character, or until input that is not
+    /// valid utf8.
+    ///
+    /// Returns what's accepted until then, but not including the matching character.
+    pub fn accept_until_char_with(&mut self, f: impl Fn(char) -> bool) -> &str {
+        let ((), start) = self.pinned_while(|ph| {
+            while let Some(c) = ph.upcoming_char() {
+                if f(c) {
+                    break;
+                }
+                ph.advance(c.len_utf8());
+            }
+        });
+
+        self.buffered_str(start, self.byte_position)
+            .unwrap_or_default()
+    }
+
+    /// Accepts until a specific character is encountered
+    ///
+    /// Returns what's accepted until then, but not including the matching character.
+    pub fn accept_until_char(&mut self, c: char) -> &str {
+        self.accept_until_char_with(|x| x == c)
+    }
+
+    /// Accepts until whitespace is encountered
+    ///
+    /// Returns what's accepted until then, but not including the whitespace
+    pub fn accept_until_whitespace(&mut self) -> &str {
+        self.accept_until_char_with(|x| x.is_whitespace())
+    }
+
+    /// Accepts a single whitespace character.
+    pub fn accept_whitespace(&mut self) -> Option<&str> {
+        self.accept_char_with(|x| x.is_whitespace())
+    }
+
+    /// Accepts a sequence of zero or more whitespace characters.
+    pub fn accept_zero_or_more_whitespace(&mut self) -> &str {
+        self.accept_until_char_with(|x| !x.is_whitespace())
+    }
+
+    /// Like [`ParseHelper::slice_accepted`](crate::ParseHelper::slice_accepted). Everything
+    /// accepted in the closure is kept in the buffer until it returns.
+    pub fn slice_accepted<P>(&mut self, closure: impl FnOnce(&mut Self) -> P) -> (P, &str) {
+        let (res, start) = self.pinned_while(closure);
+        (
+            res,
+            self.buffered_str(start, self.byte_position)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Like [`ParseHelper::slice_accepted_option`](crate::ParseHelper::slice_accepted_option):
+    /// if the closure returns `None`, the parse helper resets to where it was before.
+    pub fn slice_accepted_option(
+        &mut self,
+        closure: impl FnOnce(&mut Self) -> Option<()>,
+    ) -> Option<&str> {
+        let backup = self.create_backup();
+        let start = self.byte_position;
+
+        match closure(self) {
+            Some(()) => {
+                self.commit(backup);
+                self.buffered_str(start, self.byte_position)
+            }
+            None => {
+                self.restore_backup(backup);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+
+    use crate::StreamingParseHelper;
+
+    // hands out its input a few bytes at a time, like a socket would
+    struct Trickle<'a> {
+        input: &'a [u8],
+        step: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.step = self.step % 3 + 1;
+            let n = self.step.min(buf.len()).min(self.input.len());
+            buf[..n].copy_from_slice(&self.input[..n]);
+            self.input = &self.input[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn stream_lines() {
+        let input: String = (0..10_000).map(|i| format!("line {i}\n")).collect();
+        let mut ph = StreamingParseHelper::new_char_oriented(Trickle {
+            input: input.as_bytes(),
+            step: 0,
+        });
+        ph.set_chunk_size(64);
+
+        let mut lines = 0;
+        let mut max_buffered = 0;
+        while !ph.done() {
+            assert_eq!(ph.accept("line "), Some("line "));
+            assert_eq!(ph.accept_until_char('\n'), lines.to_string());
+            assert_eq!(ph.accept_char('\n'), Some("\n"));
+
+            lines += 1;
+            max_buffered = max_buffered.max(ph.buffered());
+        }
+
+        assert_eq!(lines, 10_000);
+        assert_eq!(ph.bytes_accepted(), input.len());
+        assert!(max_buffered <= 256, "buffered {max_buffered} bytes");
+    }
+
+    #[test]
+    fn backups_keep_input() {
+        let input = "x".repeat(1000) + "!";
+        let mut ph = StreamingParseHelper::new_byte_oriented(input.as_bytes());
+        ph.set_chunk_size(16);
+
+        let backup = ph.create_backup();
+        let start = backup.mark();
+        assert_eq!(ph.accept_until_byte(b'!').len(), 1000);
+        let end = ph.mark();
+        assert_eq!(ph.slice(start..end).map(<[u8]>::len), Some(1000));
+
+        ph.restore_backup(backup);
+        assert_eq!(ph.accept_n_bytes(1001).map(<[u8]>::len), Some(1001));
+        assert!(ph.done());
+
+        // without a backup, the input is discarded
+        assert_eq!(ph.slice(start..end), None);
+        assert!(ph.buffered() < 1000);
+
+        let mut ph = StreamingParseHelper::new_byte_oriented(input.as_bytes());
+        assert_eq!(
+            ph.slice_accepted_option(|ph| ph.accept(b"xy").map(|_| ())),
+            None
+        );
+        assert_eq!(
+            ph.slice_accepted_option(|ph| ph.accept(b"xx").map(|_| ())),
+            Some(b"xx".as_slice())
+        );
+    }
+
+    #[test]
+    fn dropped_backups_release_input() {
+        let input = "x".repeat(1000);
+        let mut ph = StreamingParseHelper::new_byte_oriented(input.as_bytes());
+        ph.set_chunk_size(16);
+
+        for _ in 0..1000 {
+            let _backup = ph.create_backup();
+            assert!(ph.accept_byte(b'x'));
+        }
+        assert!(ph.done());
+        assert!(ph.buffered() < 64, "buffered {} bytes", ph.buffered());
+
+        let mut ph = StreamingParseHelper::new_byte_oriented(input.as_bytes());
+        ph.set_chunk_size(16);
+        let backup = ph.create_backup();
+        let also_pinned = ph.create_backup();
+        assert_eq!(backup, also_pinned);
+        drop(also_pinned);
+        assert!(ph.accept_n_bytes(1000).is_some());
+        assert_eq!(
+            ph.slice(backup.mark()..ph.mark()).map(<[u8]>::len),
+            Some(1000)
+        );
+    }
+
+    #[test]
+    fn io_errors() {
+        let mut ph = StreamingParseHelper::new_char_oriented(
+            b"ab"
+                .as_slice()
+                .chain(Broken(false).chain(b"cd".as_slice())),
+        );
+
+        assert_eq!(ph.accept_until_whitespace(), "ab");
+        assert!(ph.done());
+        assert_eq!(
+            ph.io_error().map(io::Error::kind),
+            Some(io::ErrorKind::BrokenPipe)
+        );
+
+        assert!(ph.take_io_error().is_some());
+        assert_eq!(ph.accept("cd"), Some("cd"));
+    }
+
+    // fails once, and then ends
+    struct Broken(bool);
+
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            if self.0 {
+                return Ok(0);
+            }
+            self.0 = true;
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+    }
+}
@@ -1,6 +1,21 @@
 use core::str;
 
-use crate::{Char, ParseHelper};
+use crate::{
+    trace::{Closure, NoArgument},
+    Char, Mark, ParseHelper,
+};
+
+/// A human readable position in a text, as used in error messages.
+///
+/// Both the line and the column start counting at 1, and the column is counted in
+/// unicode codepoints.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct LineColumn {
+    /// The line number, starting at 1
+    pub line: usize,
+    /// The column number in codepoints, starting at 1
+    pub column: usize,
+}
 
 impl<'a, T: ?Sized> ParseHelper<'a, T, Char>
 where
@@ -8,7 +23,9 @@ where
 {
     /// Returns the remaining string, the part that has not yet been accepted
     pub fn leftover(&self) -> &'a str {
-        &AsRef::<str>::as_ref(self.input)[self.byte_position..]
+        AsRef::<str>::as_ref(self.input)
+            .get(self.byte_position..)
+            .unwrap_or_default()
     }
 
     /// returns the next character to be accepted
@@ -22,6 +39,41 @@ where
         Some(unsafe { self.leftover().chars().next().unwrap_unchecked() })
     }
 
+    /// Returns the line and column of a mark in the input.
+    ///
+    /// Marks past the end of the input are treated as if they were at the end.
+    ///
+    /// ```rust
+    /// use parse_helper::{LineColumn, ParseHelper};
+    ///
+    /// let mut ph = ParseHelper::new_char_oriented("ab\ncd");
+    /// ph.accept("ab\nc");
+    ///
+    /// assert_eq!(ph.line_column(ph.mark()), LineColumn { line: 2, column: 2 });
+    /// ```
+    pub fn line_column(&self, mark: Mark<Char>) -> LineColumn {
+        let input = AsRef::<str>::as_ref(self.input);
+        let before = input.get(..mark.byte_position()).unwrap_or(input);
+
+        let (line, line_start) = match before.rfind('\n') {
+            Some(idx) => (
+                before.matches('\n').count().saturating_add(1),
+                idx.saturating_add(1),
+            ),
+            None => (1, 0),
+        };
+
+        LineColumn {
+            line,
+            column: before
+                .get(line_start..)
+                .unwrap_or_default()
+                .chars()
+                .count()
+                .saturating_add(1),
+        }
+    }
+
     /// Accepts a sequence of string-like values from the input.
     ///
     /// Returns a string slice containing the same things that were asked to be accepted,
@@ -38,13 +90,16 @@ where
     /// ```
     ///
     pub fn accept(&mut self, str: impl AsRef<str>) -> Option<&'a str> {
-        // Safety: bytes contains utf8 encoded characters, so after accepting it we
-        // must have accepted a number of complete utf8 codepoints making us end up
-        // at another boundary.
-        unsafe { self.as_byte_oriented_mut().accept(str.as_ref().as_bytes()) }
-            // Safety: what we get back is the exact sequence of bytes we accepted,
-            // which we know is equal to some utf8 encoded string so this is valid
-            .map(|x| unsafe { str::from_utf8_unchecked(x) })
+        let str = str.as_ref();
+        self.traced("accept", &str, |ph| {
+            // Safety: bytes contains utf8 encoded characters, so after accepting it we
+            // must have accepted a number of complete utf8 codepoints making us end up
+            // at another boundary.
+            unsafe { ph.as_byte_oriented_mut().accept(str.as_bytes()) }
+                // Safety: what we get back is the exact sequence of bytes we accepted,
+                // which we know is equal to some utf8 encoded string so this is valid
+                .map(|x| unsafe { str::from_utf8_unchecked(x) })
+        })
     }
 
     /// Accepts a byte if the passed closure evaluates to true.
@@ -64,42 +119,46 @@ where
     /// assert_eq!(ph.accept_char_with(|x| true), None);
     /// ```
     pub fn accept_char_with(&mut self, f: impl Fn(char) -> bool) -> Option<&'a str> {
-        let next_char = self.upcoming_char()?;
-        if f(next_char) {
-            let old_pos = self.byte_position;
-            self.byte_position += next_char.len_utf8();
-            Some(unsafe {
-                AsRef::<str>::as_ref(self.input).get_unchecked(old_pos..self.byte_position)
-            })
-        } else {
-            None
-        }
+        self.traced("accept_char_with", &Closure, |ph| {
+            let next_char = ph.upcoming_char()?;
+            if f(next_char) {
+                let old_pos = ph.byte_position;
+                ph.advance(next_char.len_utf8());
+                Some(unsafe {
+                    AsRef::<str>::as_ref(ph.input).get_unchecked(old_pos..ph.byte_position)
+                })
+            } else {
+                None
+            }
+        })
     }
 
     /// Accepts until the closure matches the current character.
     ///
     /// Returns what's accepted until then, but not including the matching character.
     pub fn accept_until_char_with(&mut self, f: impl Fn(char) -> bool) -> &'a str {
-        let start = self.byte_position;
+        self.traced("accept_until_char_with", &Closure, |ph| {
+            let start = ph.byte_position;
 
-        // while it doesn't match...
-        while let Some(next_char) = self.upcoming_char() {
-            if f(next_char) {
-                break;
-            }
+            // while it doesn't match...
+            while let Some(next_char) = ph.upcoming_char() {
+                if f(next_char) {
+                    break;
+                }
 
-            self.byte_position += next_char.len_utf8();
-        }
+                ph.advance(next_char.len_utf8());
+            }
 
-        let end = self.byte_position;
+            let end = ph.byte_position;
 
-        // * `begin` must not exceed `end`. (we accepted 0 or more characters)
-        // * `begin` and `end` must be byte positions within the string slice. (byte_position
-        //   starts at 0 and never goes out of bounds, `upcoming_char` tests this)
-        // * `begin` and `end` must lie on UTF-8 sequence boundaries. Here we can assume
-        //   all aour offsets are on utf8 boundaries because of the boundary assumption generic
-        //   paramter
-        unsafe { AsRef::<str>::as_ref(self.input).get_unchecked(start..end) }
+            // * `begin` must not exceed `end`. (we accepted 0 or more characters)
+            // * `begin` and `end` must be byte positions within the string slice. (byte_position
+            //   starts at 0 and never goes out of bounds, `upcoming_char` tests this)
+            // * `begin` and `end` must lie on UTF-8 sequence boundaries. Here we can assume
+            //   all aour offsets are on utf8 boundaries because of the boundary assumption generic
+            //   paramter
+            unsafe { AsRef::<str>::as_ref(ph.input).get_unchecked(start..end) }
+        })
     }
 
     /// accepts a single char from the input. Assumes the encoding is utf8.
@@ -116,40 +175,50 @@ where
     /// assert_eq!(ph.accept_char('d'), None);
     /// ```
     pub fn accept_char(&mut self, c: char) -> Option<&'a str> {
-        self.accept_char_with(|x| x == c)
+        self.traced("accept_char", &c, |ph| ph.accept_char_with(|x| x == c))
     }
 
     /// Accepts until a specific character is encountered
     ///
     /// Returns what's accepted until then, but not including the matching character.
     pub fn accept_until_char(&mut self, c: char) -> &'a str {
-        self.accept_until_char_with(|x| x == c)
+        self.traced("accept_until_char", &c, |ph| {
+            ph.accept_until_char_with(|x| x == c)
+        })
     }
 
     /// Accepts until whitespace is encountered
     ///
     /// Returns what's accepted until then, but not including the whitespace
     pub fn accept_until_whitespace(&mut self) -> &'a str {
-        self.accept_until_char_with(|x| x.is_whitespace())
+        self.traced("accept_until_whitespace", &NoArgument, |ph| {
+            ph.accept_until_char_with(|x| x.is_whitespace())
+        })
     }
 
     /// Accepts a single whitespace character.
     pub fn accept_whitespace(&mut self) -> Option<&'a str> {
-        self.accept_char_with(|i| i.is_whitespace())
+        self.traced("accept_whitespace", &NoArgument, |ph| {
+            ph.accept_char_with(|i| i.is_whitespace())
+        })
     }
 
     /// Accepts a sequence of zero or more whitespace characters.
     pub fn accept_zero_or_more_whitespace(&mut self) -> &'a str {
-        self.accept_until_char_with(|x| !x.is_whitespace())
+        self.traced("accept_zero_or_more_whitespace", &NoArgument, |ph| {
+            ph.accept_until_char_with(|x| !x.is_whitespace())
+        })
     }
 
     /// Accepts a sequence of one or more whitespace characters.
     pub fn accept_one_or_more_whitespace(&mut self) -> Option<&'a str> {
-        if !self.upcoming_char()?.is_whitespace() {
-            return None;
-        }
+        self.traced("accept_one_or_more_whitespace", &NoArgument, |ph| {
+            if !ph.upcoming_char()?.is_whitespace() {
+                return None;
+            }
 
-        Some(self.accept_until_char_with(|x| !x.is_whitespace()))
+            Some(ph.accept_until_char_with(|x| !x.is_whitespace()))
+        })
     }
 }
 
@@ -0,0 +1,65 @@
+use core::fmt::{self, Display, Formatter};
+
+use crate::{Char, LineColumn, Mark, ParseHelper};
+
+/// Error returned when reading one of the text formats, like JSON or CSV, fails.
+///
+/// [`JsonError`](crate::JsonError) and [`CsvError`](crate::CsvError) are this type.
+///
+/// ```
+/// use parse_helper::{LineColumn, ParseHelper};
+///
+/// let err = ParseHelper::new_char_oriented("[1,\n 2 3]").validate_json().unwrap_err();
+/// assert_eq!(err.message(), "expected `,` or `]`");
+/// assert_eq!(err.mark().byte_position(), 7);
+/// assert_eq!(err.line_column(), LineColumn { line: 2, column: 4 });
+/// assert_eq!(err.to_string(), "expected `,` or `]` at line 2 column 4");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SyntaxError {
+    message: &'static str,
+    mark: Mark<Char>,
+    line_column: LineColumn,
+}
+
+impl SyntaxError {
+    /// What went wrong.
+    pub fn message(&self) -> &'static str {
+        self.message
+    }
+
+    /// The position in the input where it went wrong.
+    pub fn mark(&self) -> Mark<Char> {
+        self.mark
+    }
+
+    /// The line and column in the input where it went wrong.
+    pub fn line_column(&self) -> LineColumn {
+        self.line_column
+    }
+}
+
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let LineColumn { line, column } = self.line_column;
+        write!(f, "{} at line {line} column {column}", self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SyntaxError {}
+
+impl<T: ?Sized> ParseHelper<'_, T, Char>
+where
+    T: AsRef<str> + AsRef<[u8]>,
+{
+    // an error at a position in the input of this parse helper
+    pub(crate) fn syntax_error(&self, byte_position: usize, message: &'static str) -> SyntaxError {
+        let mark = Mark::new(byte_position);
+        SyntaxError {
+            message,
+            mark,
+            line_column: self.line_column(mark),
+        }
+    }
+}
@@ -0,0 +1,438 @@
+use core::fmt::{self, Debug, Formatter};
+
+#[cfg(feature = "trace")]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(feature = "trace")]
+use core::{
+    cell::{Cell, RefCell},
+    fmt::Display,
+};
+
+use crate::ParseHelper;
+
+// whether a traced method succeeded, judged by what it returned
+#[cfg_attr(not(feature = "trace"), allow(dead_code))]
+pub(crate) trait Outcome {
+    fn succeeded(&self) -> bool;
+}
+
+impl<T> Outcome for Option<T> {
+    fn succeeded(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl<T, E> Outcome for Result<T, E> {
+    fn succeeded(&self) -> bool {
+        self.is_ok()
+    }
+}
+
+impl Outcome for bool {
+    fn succeeded(&self) -> bool {
+        *self
+    }
+}
+
+// `accept_until*` methods always succeed, they return what they accepted
+impl<T: ?Sized> Outcome for &T {
+    fn succeeded(&self) -> bool {
+        true
+    }
+}
+
+// the arguments of traced methods, as they show up in a trace
+pub(crate) struct NoArgument;
+
+impl Debug for NoArgument {
+    fn fmt(&self, _: &mut Formatter<'_>) -> fmt::Result {
+        Ok(())
+    }
+}
+
+pub(crate) struct Closure;
+
+impl Debug for Closure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "|..|")
+    }
+}
+
+pub(crate) struct ByteLiteral(pub u8);
+
+impl Debug for ByteLiteral {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "b'{}'", self.0.escape_ascii())
+    }
+}
+
+pub(crate) struct ByteStrLiteral<'b>(pub &'b [u8]);
+
+impl Debug for ByteStrLiteral<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "b\"{}\"", self.0.escape_ascii())
+    }
+}
+
+/// A single call recorded by a [`Tracer`].
+#[cfg(feature = "trace")]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TraceEntry {
+    method: &'static str,
+    argument: String,
+    start: usize,
+    end: usize,
+    success: bool,
+    depth: usize,
+}
+
+#[cfg(feature = "trace")]
+impl TraceEntry {
+    /// The name of the method that was called, like `accept_char`.
+    pub fn method(&self) -> &'static str {
+        self.method
+    }
+
+    /// The argument the method was called with, formatted with [`Debug`].
+    /// Closures show up as `|..|`, and methods without arguments have an empty argument.
+    pub fn argument(&self) -> &str {
+        &self.argument
+    }
+
+    /// The byte position the call started at.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// The byte position the call ended at.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Whether the call succeeded.
+    pub fn success(&self) -> bool {
+        self.success
+    }
+
+    /// In how many closures of [`ParseHelper::slice_accepted_option`] this call was nested.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+#[cfg(feature = "trace")]
+impl Display for TraceEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:indent$}{}({}) {}..{} {}",
+            "",
+            self.method,
+            self.argument,
+            self.start,
+            self.end,
+            if self.success { "ok" } else { "failed" },
+            indent = self.depth.saturating_mul(2),
+        )
+    }
+}
+
+/// Records every `accept*` call of a parse helper, to debug parsers with.
+///
+/// Attach it to a parse helper with [`ParseHelper::record_trace`]. Calls made from the closure of
+/// [`slice_accepted_option`](ParseHelper::slice_accepted_option) are nested under it.
+/// Only the methods called directly are recorded, not the ones they use internally.
+///
+/// ```
+/// use parse_helper::{ParseHelper, Tracer};
+///
+/// let tracer = Tracer::new();
+/// let mut ph = ParseHelper::new_char_oriented("let x;");
+/// ph.record_trace(&tracer);
+///
+/// ph.accept("let");
+/// ph.accept_one_or_more_whitespace();
+/// ph.slice_accepted_option(|ph| {
+///     ph.accept_until_char(';');
+///     ph.accept_char('=')?;
+///     Some(())
+/// });
+///
+/// tracer.assert_trace(r#"
+///     accept("let") 0..3 ok
+///     accept_one_or_more_whitespace() 3..4 ok
+///     slice_accepted_option(|..|) 4..5 failed
+///       accept_until_char(';') 4..5 ok
+///       accept_char('=') 5..5 failed
+/// "#);
+/// ```
+#[cfg(feature = "trace")]
+#[derive(Debug, Default)]
+pub struct Tracer {
+    entries: RefCell<Vec<TraceEntry>>,
+    depth: Cell<usize>,
+    // set while a traced method runs, so the methods it calls aren't recorded
+    inside_method: Cell<bool>,
+}
+
+#[cfg(feature = "trace")]
+impl Tracer {
+    /// Creates an empty tracer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All calls recorded sofar, in the order they were made.
+    pub fn entries(&self) -> Vec<TraceEntry> {
+        self.entries.borrow().clone()
+    }
+
+    /// Forget all recorded calls.
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+    }
+
+    /// Renders the trace as an indented text tree, with one call on every line.
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+
+    /// Asserts that the rendered trace equals `expected`.
+    ///
+    /// Leading and trailing empty lines and the common indentation of `expected` are ignored,
+    /// so it can be written as an indented raw string literal.
+    ///
+    /// # Panics
+    ///
+    /// When the trace is different from what was expected.
+    #[track_caller]
+    pub fn assert_trace(&self, expected: &str) {
+        assert_eq!(
+            self.render(),
+            dedent(expected),
+            "the trace is different from what was expected"
+        );
+    }
+
+    fn begin(&self, method: &'static str, argument: &dyn Debug, start: usize) -> usize {
+        let mut entries = self.entries.borrow_mut();
+        entries.push(TraceEntry {
+            method,
+            argument: format!("{argument:?}"),
+            start,
+            end: start,
+            success: false,
+            depth: self.depth.get(),
+        });
+        entries.len().saturating_sub(1)
+    }
+
+    fn end(&self, idx: usize, end: usize, success: bool) {
+        if let Some(entry) = self.entries.borrow_mut().get_mut(idx) {
+            entry.end = end;
+            entry.success = success;
+        }
+    }
+}
+
+#[cfg(feature = "trace")]
+impl Display for Tracer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for entry in self.entries.borrow().iter() {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+// puts the tracer back in the state it was in before a traced method started, also when
+// the method (or a closure passed to it) panics. Otherwise a caught panic would leave the
+// tracer muted.
+#[cfg(feature = "trace")]
+struct RestoreOnDrop<'t> {
+    tracer: &'t Tracer,
+    depth: usize,
+    inside_method: bool,
+}
+
+#[cfg(feature = "trace")]
+impl<'t> RestoreOnDrop<'t> {
+    fn new(tracer: &'t Tracer) -> Self {
+        Self {
+            tracer,
+            depth: tracer.depth.get(),
+            inside_method: tracer.inside_method.get(),
+        }
+    }
+}
+
+#[cfg(feature = "trace")]
+impl Drop for RestoreOnDrop<'_> {
+    fn drop(&mut self) {
+        self.tracer.depth.set(self.depth);
+        self.tracer.inside_method.set(self.inside_method);
+    }
+}
+
+#[cfg(feature = "trace")]
+fn dedent(text: &str) -> String {
+    let lines: Vec<_> = text
+        .lines()
+        .map(str::trim_end)
+        .skip_while(|line| line.is_empty())
+        .collect();
+    let lines = match lines.iter().rposition(|line| !line.is_empty()) {
+        Some(last) => lines.get(..=last).unwrap_or_default(),
+        None => &[],
+    };
+
+    let indent = lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.len().saturating_sub(line.trim_start().len()))
+        .min()
+        .unwrap_or(0);
+
+    lines
+        .iter()
+        .map(|line| format!("{}\n", line.get(indent..).unwrap_or_default()))
+        .collect()
+}
+
+impl<'a, T: ?Sized, B> ParseHelper<'a, T, B> {
+    /// Records every `accept*` call of this parse helper, and all its backups, in `tracer`.
+    /// See [`Tracer`].
+    #[cfg(feature = "trace")]
+    pub fn record_trace(&mut self, tracer: &'a Tracer) {
+        self.tracer = Some(tracer);
+    }
+
+    // runs a public parsing method, recording it in the tracer if there is one
+    #[inline]
+    pub(crate) fn traced<R: Outcome>(
+        &mut self,
+        method: &'static str,
+        argument: &dyn Debug,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        #[cfg(feature = "trace")]
+        if let Some(tracer) = self.tracer.filter(|t| !t.inside_method.get()) {
+            let idx = tracer.begin(method, argument, self.byte_position);
+            let _restore = RestoreOnDrop::new(tracer);
+            tracer.inside_method.set(true);
+            let res = f(self);
+            tracer.end(idx, self.byte_position, res.succeeded());
+            return res;
+        }
+
+        let _ = (method, argument);
+        f(self)
+    }
+
+    // like `traced`, but the calls made by the closure are recorded nested under this one
+    #[inline]
+    pub(crate) fn traced_scope<R, S: Outcome>(
+        &mut self,
+        method: &'static str,
+        f: impl FnOnce(&mut Self) -> R,
+        outcome: impl FnOnce(&R) -> &S,
+    ) -> R {
+        #[cfg(feature = "trace")]
+        if let Some(tracer) = self.tracer.filter(|t| !t.inside_method.get()) {
+            let idx = tracer.begin(method, &Closure, self.byte_position);
+            let restore = RestoreOnDrop::new(tracer);
+            tracer.depth.set(restore.depth.saturating_add(1));
+            let res = f(self);
+            drop(restore);
+            tracer.end(idx, self.byte_position, outcome(&res).succeeded());
+            return res;
+        }
+
+        let _ = (method, outcome);
+        f(self)
+    }
+}
+
+#[cfg(all(test, feature = "trace"))]
+mod tests {
+    use crate::{ParseHelper, Tracer};
+
+    #[test]
+    fn trace_nested() {
+        let tracer = Tracer::new();
+        let mut ph = ParseHelper::new_byte_oriented(b"\x01ab\n");
+        ph.record_trace(&tracer);
+
+        ph.accept_u8();
+        ph.slice_accepted_option(|ph| {
+            ph.accept(b"a")?;
+            ph.slice_accepted_option(|ph| ph.accept_byte(b'b').then_some(()))?;
+            ph.accept_until_byte_with(|b| b == b'\n');
+            ph.accept_byte(b'\t').then_some(())
+        });
+        let backup = ph.create_backup();
+        ph.accept_u16_le();
+        ph.restore_backup(backup);
+
+        tracer.assert_trace(
+            r#"
+            accept_u8() 0..1 ok
+            slice_accepted_option(|..|) 1..3 failed
+              accept(b"a") 1..2 ok
+              slice_accepted_option(|..|) 2..3 ok
+                accept_byte(b'b') 2..3 ok
+              accept_until_byte_with(|..|) 3..3 ok
+              accept_byte(b'\t') 3..3 failed
+            accept_u16_le() 1..3 ok
+            "#,
+        );
+
+        assert_eq!(tracer.entries()[1].depth(), 0);
+        assert_eq!(tracer.entries()[2].argument(), "b\"a\"");
+        assert!(!tracer.entries()[6].success());
+
+        tracer.clear();
+        assert_eq!(tracer.render(), "");
+    }
+
+    #[test]
+    fn trace_after_panic() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let tracer = Tracer::new();
+        let mut ph = ParseHelper::new_char_oriented("ab");
+        ph.record_trace(&tracer);
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            ph.slice_accepted_option(|ph| {
+                ph.accept_char_with(|_| panic!("oops"));
+                Some(())
+            })
+        }));
+        assert!(res.is_err());
+
+        ph.accept_char('a');
+        tracer.assert_trace(
+            r#"
+            slice_accepted_option(|..|) 0..0 failed
+              accept_char_with(|..|) 0..0 failed
+            accept_char('a') 0..1 ok
+            "#,
+        );
+    }
+
+    #[test]
+    #[should_panic = "the trace is different from what was expected"]
+    fn trace_mismatch() {
+        let tracer = Tracer::new();
+        let mut ph = ParseHelper::new_char_oriented("a");
+        ph.record_trace(&tracer);
+        ph.accept_char('a');
+
+        tracer.assert_trace("accept_char('b') 0..1 ok");
+    }
+}
@@ -0,0 +1,387 @@
+use core::fmt::{self, Display, Formatter};
+
+use crate::{trace::NoArgument, Byte, Mark, ParseHelper};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Unsigned integer types that variable length integers can be decoded into.
+///
+/// This trait is sealed, and implemented for all unsigned primitive integers except `usize`.
+pub trait UnsignedVarInt: private::Sealed + Sized {
+    #[doc(hidden)]
+    const BITS: u32;
+
+    #[doc(hidden)]
+    fn from_bits(bits: u128) -> Self;
+}
+
+/// Signed integer types that variable length integers can be decoded into.
+///
+/// This trait is sealed, and implemented for all signed primitive integers except `isize`.
+pub trait SignedVarInt: private::Sealed + Sized {
+    #[doc(hidden)]
+    const BITS: u32;
+
+    #[doc(hidden)]
+    fn from_bits(bits: i128) -> Self;
+}
+
+macro_rules! impl_varint {
+    ($trait: ident, $bits_ty: ty: $($ty: ty),*) => {
+        $(
+            impl private::Sealed for $ty {}
+            impl $trait for $ty {
+                const BITS: u32 = <$ty>::BITS;
+
+                fn from_bits(bits: $bits_ty) -> Self {
+                    // the decoders make sure the value fits in `BITS` bits.
+                    bits as $ty
+                }
+            }
+        )*
+    };
+}
+
+impl_varint!(UnsignedVarInt, u128: u8, u16, u32, u64, u128);
+impl_varint!(SignedVarInt, i128: i8, i16, i32, i64, i128);
+
+/// The ways in which decoding a variable length integer can fail.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum VarIntErrorKind {
+    /// The input ended before the last byte of the integer.
+    UnexpectedEnd,
+    /// The integer was encoded in more bytes than the target type could ever need.
+    Overlong,
+    /// The encoded value does not fit in the target type.
+    Overflow,
+}
+
+/// Error returned when a variable length integer could not be decoded.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct VarIntError {
+    kind: VarIntErrorKind,
+    start: Mark<Byte>,
+}
+
+impl VarIntError {
+    /// What went wrong while decoding.
+    pub fn kind(&self) -> VarIntErrorKind {
+        self.kind
+    }
+
+    /// The position in the input where the integer started.
+    pub fn mark(&self) -> Mark<Byte> {
+        self.start
+    }
+}
+
+impl Display for VarIntError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let msg = match self.kind {
+            VarIntErrorKind::UnexpectedEnd => "end of input reached while decoding integer",
+            VarIntErrorKind::Overlong => "overlong integer encoding",
+            VarIntErrorKind::Overflow => "integer does not fit in the target type",
+        };
+
+        write!(f, "{msg} at byte {}", self.start.byte_position())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VarIntError {}
+
+impl<'a, T: ?Sized> ParseHelper<'a, T, Byte>
+where
+    T: AsRef<[u8]>,
+{
+    /// Runs a decoder, and resets the parse helper when the decoder fails.
+    fn decode_varint<R>(
+        &mut self,
+        method: &'static str,
+        decoder: impl FnOnce(&mut Self) -> Result<R, VarIntErrorKind>,
+    ) -> Result<R, VarIntError> {
+        self.traced(method, &NoArgument, |ph| {
+            let old = ph.create_backup();
+
+            decoder(ph).map_err(|kind| {
+                ph.restore_backup(old);
+                VarIntError {
+                    kind,
+                    start: ph.mark(),
+                }
+            })
+        })
+    }
+
+    /// Decodes the raw bits of an unsigned LEB128 encoded integer that has to fit in `bits` bits.
+    fn decode_uleb128(&mut self, bits: u32) -> Result<u128, VarIntErrorKind> {
+        let mut res = 0;
+
+        for shift in (0..bits).step_by(7) {
+            let byte = self.accept_u8().ok_or(VarIntErrorKind::UnexpectedEnd)?;
+            let payload = u128::from(byte & 0x7f);
+
+            // in the last byte, only the lower bits may be used.
+            let remaining = bits.saturating_sub(shift);
+            if remaining < 7 && payload >> remaining != 0 {
+                return Err(VarIntErrorKind::Overflow);
+            }
+
+            res |= payload << shift;
+            if byte & 0x80 == 0 {
+                return Ok(res);
+            }
+        }
+
+        Err(VarIntErrorKind::Overlong)
+    }
+
+    /// Decodes a signed LEB128 encoded integer that has to fit in `bits` bits.
+    fn decode_sleb128(&mut self, bits: u32) -> Result<i128, VarIntErrorKind> {
+        let mut res = 0;
+
+        for shift in (0..bits).step_by(7) {
+            let byte = self.accept_u8().ok_or(VarIntErrorKind::UnexpectedEnd)?;
+            let payload = u128::from(byte & 0x7f);
+
+            // in the last byte, the sign bit and all bits above it must be equal.
+            let remaining = bits.saturating_sub(shift);
+            if remaining < 7 {
+                // remaining is at least one, as shift is less than bits
+                let sign_and_above = payload >> remaining.saturating_sub(1);
+                let all_set = 0x7f >> remaining.saturating_sub(1);
+
+                if sign_and_above != 0 && sign_and_above != all_set {
+                    return Err(VarIntErrorKind::Overflow);
+                }
+            }
+
+            res |= payload << shift;
+            if byte & 0x80 == 0 {
+                // sign extend
+                let end = shift.saturating_add(7);
+                if end < u128::BITS && byte & 0x40 != 0 {
+                    res |= u128::MAX << end;
+                }
+
+                return Ok(res as i128);
+            }
+        }
+
+        Err(VarIntErrorKind::Overlong)
+    }
+
+    /// Accepts an unsigned [LEB128](https://en.wikipedia.org/wiki/LEB128) encoded integer,
+    /// as used by WebAssembly and DWARF. Protobuf's varints use the same encoding.
+    ///
+    /// The encoding may be padded with `0x80` bytes, as long as it doesn't take up more bytes
+    /// than any value of `I` could need. On failure, nothing is accepted.
+    ///
+    /// ```rust
+    /// use parse_helper::{ParseHelper, VarIntErrorKind};
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented(b"\xe5\x8e\x26\x80\x00\x80\x02");
+    ///
+    /// assert_eq!(ph.accept_uleb128::<u32>(), Ok(624485));
+    /// assert_eq!(ph.accept_uleb128::<u8>(), Ok(0));
+    /// assert_eq!(ph.accept_uleb128::<u8>().unwrap_err().kind(), VarIntErrorKind::Overflow);
+    /// assert_eq!(ph.accept_uleb128::<u16>(), Ok(256));
+    /// ```
+    pub fn accept_uleb128<I: UnsignedVarInt>(&mut self) -> Result<I, VarIntError> {
+        self.decode_varint("accept_uleb128", |ph| ph.decode_uleb128(I::BITS))
+            .map(I::from_bits)
+    }
+
+    /// Accepts a signed [LEB128](https://en.wikipedia.org/wiki/LEB128) encoded integer,
+    /// as used by WebAssembly and DWARF.
+    ///
+    /// The encoding may be padded with sign extension bytes, as long as it doesn't take up more
+    /// bytes than any value of `I` could need. On failure, nothing is accepted.
+    ///
+    /// ```rust
+    /// use parse_helper::{ParseHelper, VarIntErrorKind};
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented(b"\xc0\xbb\x78\x7f\x80\x7e");
+    ///
+    /// assert_eq!(ph.accept_sleb128::<i32>(), Ok(-123456));
+    /// assert_eq!(ph.accept_sleb128::<i8>(), Ok(-1));
+    /// assert_eq!(ph.accept_sleb128::<i8>().unwrap_err().kind(), VarIntErrorKind::Overflow);
+    /// assert_eq!(ph.accept_sleb128::<i16>(), Ok(-256));
+    /// ```
+    pub fn accept_sleb128<I: SignedVarInt>(&mut self) -> Result<I, VarIntError> {
+        self.decode_varint("accept_sleb128", |ph| ph.decode_sleb128(I::BITS))
+            .map(I::from_bits)
+    }
+
+    /// Accepts a protobuf style zigzag encoded varint (`sint32` and `sint64`).
+    ///
+    /// Zigzag encoding maps signed integers to unsigned ones so that numbers with a small
+    /// magnitude have a short encoding (`0, -1, 1, -2, ...` become `0, 1, 2, 3, ...`),
+    /// which is then encoded like [`accept_uleb128`](Self::accept_uleb128).
+    ///
+    /// ```rust
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented(b"\x00\x01\x02\x03\xfe\xff\xff\xff\x0f");
+    ///
+    /// assert_eq!(ph.accept_varint_zigzag::<i64>(), Ok(0));
+    /// assert_eq!(ph.accept_varint_zigzag::<i64>(), Ok(-1));
+    /// assert_eq!(ph.accept_varint_zigzag::<i64>(), Ok(1));
+    /// assert_eq!(ph.accept_varint_zigzag::<i64>(), Ok(-2));
+    /// assert_eq!(ph.accept_varint_zigzag::<i32>(), Ok(i32::MAX));
+    /// ```
+    pub fn accept_varint_zigzag<I: SignedVarInt>(&mut self) -> Result<I, VarIntError> {
+        self.decode_varint("accept_varint_zigzag", |ph| ph.decode_uleb128(I::BITS))
+            .map(|x| I::from_bits(((x >> 1) ^ (x & 1).wrapping_neg()) as i128))
+    }
+
+    /// Accepts a big endian variable length integer, as used for the offsets of
+    /// `OFS_DELTA` objects in Git packfiles.
+    ///
+    /// Every byte holds 7 bits of the value, most significant group first, and the high bit
+    /// is set on all bytes but the last. Every continuation also adds one to the value, so
+    /// every value has exactly one encoding and overlong encodings can't occur.
+    /// On failure, nothing is accepted.
+    ///
+    /// ```rust
+    /// use parse_helper::ParseHelper;
+    ///
+    /// let mut ph = ParseHelper::new_byte_oriented(b"\x7f\x80\x00\x81\x7f");
+    ///
+    /// assert_eq!(ph.accept_varint_be::<u64>(), Ok(127));
+    /// assert_eq!(ph.accept_varint_be::<u64>(), Ok(128));
+    /// assert_eq!(ph.accept_varint_be::<u64>(), Ok(383));
+    /// ```
+    pub fn accept_varint_be<I: UnsignedVarInt>(&mut self) -> Result<I, VarIntError> {
+        self.decode_varint("accept_varint_be", |ph| {
+            let mut byte = ph.accept_u8().ok_or(VarIntErrorKind::UnexpectedEnd)?;
+            let mut res = u128::from(byte & 0x7f);
+
+            while byte & 0x80 != 0 {
+                byte = ph.accept_u8().ok_or(VarIntErrorKind::UnexpectedEnd)?;
+
+                // for u128, res can be u128::MAX already, so adding one can overflow too
+                res = res
+                    .checked_add(1)
+                    .filter(|res| res >> I::BITS.saturating_sub(7) == 0)
+                    .ok_or(VarIntErrorKind::Overflow)?;
+                res = (res << 7) | u128::from(byte & 0x7f);
+            }
+
+            Ok(res)
+        })
+        .map(I::from_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ParseHelper, VarIntErrorKind};
+
+    #[test]
+    fn no_panics_at_end_of_input() {
+        for input in ["", "a", "\u{e9}", "a\u{1f980}"] {
+            let mut ph = ParseHelper::new_byte_oriented(input);
+            ph.skip_bytes(input.len());
+
+            assert!(ph.accept_uleb128::<u128>().is_err());
+            assert!(ph.accept_sleb128::<i8>().is_err());
+            assert!(ph.accept_varint_zigzag::<i64>().is_err());
+            assert!(ph.accept_varint_be::<u16>().is_err());
+            assert!(ph.done());
+        }
+    }
+
+    #[test]
+    fn uleb128_limits() {
+        let mut ph = ParseHelper::new_byte_oriented(b"\xff\xff\xff\xff\x0f");
+        assert_eq!(ph.accept_uleb128::<u32>(), Ok(u32::MAX));
+
+        let mut ph = ParseHelper::new_byte_oriented(b"\xff\xff\xff\xff\x1f");
+        assert_eq!(
+            ph.accept_uleb128::<u32>().unwrap_err().kind(),
+            VarIntErrorKind::Overflow
+        );
+        // nothing is accepted on failure
+        assert_eq!(ph.bytes_left(), 5);
+
+        let mut ph = ParseHelper::new_byte_oriented(b"\x80\x80\x80\x80\x80\x00");
+        assert_eq!(
+            ph.accept_uleb128::<u32>().unwrap_err().kind(),
+            VarIntErrorKind::Overlong
+        );
+
+        let mut ph = ParseHelper::new_byte_oriented(b"\x80\x80\x80\x80\x00");
+        assert_eq!(ph.accept_uleb128::<u32>(), Ok(0));
+
+        let mut bytes = [0xff; 19];
+        bytes[18] = 0x03;
+        let mut ph = ParseHelper::new_byte_oriented(&bytes);
+        assert_eq!(ph.accept_uleb128::<u128>(), Ok(u128::MAX));
+    }
+
+    #[test]
+    fn sleb128_limits() {
+        let mut ph = ParseHelper::new_byte_oriented(b"\x80\x80\x80\x80\x78");
+        assert_eq!(ph.accept_sleb128::<i32>(), Ok(i32::MIN));
+
+        let mut ph = ParseHelper::new_byte_oriented(b"\xff\xff\xff\xff\x07");
+        assert_eq!(ph.accept_sleb128::<i32>(), Ok(i32::MAX));
+
+        let mut ph = ParseHelper::new_byte_oriented(b"\xff\xff\xff\xff\x0f");
+        assert_eq!(
+            ph.accept_sleb128::<i32>().unwrap_err().kind(),
+            VarIntErrorKind::Overflow
+        );
+
+        let mut ph = ParseHelper::new_byte_oriented(b"\x80\x80\x80\x80\x80\x80\x80\x80\x80\x7f");
+        assert_eq!(ph.accept_sleb128::<i64>(), Ok(i64::MIN));
+
+        let mut bytes = [0x80; 19];
+        bytes[18] = 0x7e;
+        let mut ph = ParseHelper::new_byte_oriented(&bytes);
+        assert_eq!(ph.accept_sleb128::<i128>(), Ok(i128::MIN));
+    }
+
+    #[test]
+    fn varint_errors_report_start() {
+        let mut ph = ParseHelper::new_byte_oriented(b"\x01\x80\x80");
+        assert_eq!(ph.accept_uleb128::<u64>(), Ok(1));
+
+        let err = ph.accept_uleb128::<u64>().unwrap_err();
+        assert_eq!(err.kind(), VarIntErrorKind::UnexpectedEnd);
+        assert_eq!(err.mark().byte_position(), 1);
+        assert_eq!(ph.bytes_left(), 2);
+    }
+
+    #[test]
+    fn varint_be() {
+        let mut ph = ParseHelper::new_byte_oriented(b"\x80\x7f\x81\x00");
+        assert_eq!(ph.accept_varint_be::<u8>(), Ok(255));
+        assert_eq!(
+            ph.accept_varint_be::<u8>().unwrap_err().kind(),
+            VarIntErrorKind::Overflow
+        );
+        assert_eq!(ph.accept_varint_be::<u16>(), Ok(256));
+    }
+
+    #[test]
+    fn varint_be_u128() {
+        let max = b"\x82\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\x7f";
+        let mut ph = ParseHelper::new_byte_oriented(max);
+        assert_eq!(ph.accept_varint_be::<u128>(), Ok(u128::MAX));
+        assert!(ph.done());
+
+        // u128::MAX followed by another continuation
+        let too_large =
+            b"\x82\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xfe\xff\x00";
+        let mut ph = ParseHelper::new_byte_oriented(too_large);
+        assert_eq!(
+            ph.accept_varint_be::<u128>().unwrap_err().kind(),
+            VarIntErrorKind::Overflow
+        );
+        assert_eq!(ph.bytes_left(), too_large.len());
+    }
+}
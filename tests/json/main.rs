@@ -0,0 +1,102 @@
+//! Runs the JSON parser on the cases in `test_parsing`, see the README next to this file.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    fs,
+    path::Path,
+};
+
+use parse_helper::ParseHelper;
+
+// counts the allocations on each thread, to check that validating doesn't allocate
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[test]
+fn test_parsing() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/json/test_parsing");
+    let mut failures = Vec::new();
+    let mut cases = 0;
+
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_str().unwrap().to_owned();
+        let bytes = fs::read(&path).unwrap();
+        cases += 1;
+
+        // the parser works on strings, so invalid utf8 is rejected before it gets there
+        let Ok(input) = std::str::from_utf8(&bytes) else {
+            if name.starts_with("y_") {
+                failures.push(format!("{name}: not utf8"));
+            }
+            continue;
+        };
+
+        let mut ph = ParseHelper::new_char_oriented(input);
+        let before = allocations();
+        let validated = ph.validate_json();
+        if allocations() != before {
+            failures.push(format!("{name}: validating allocated"));
+        }
+        let position_ok = match validated {
+            Ok(()) => ph.done(),
+            Err(_) => ph.bytes_accepted() == 0,
+        };
+        if !position_ok {
+            failures.push(format!("{name}: wrong position after validating"));
+        }
+
+        let mut ph = ParseHelper::new_char_oriented(input);
+        let parsed = ph.parse_json();
+        if parsed.as_ref().err() != validated.as_ref().err() {
+            failures.push(format!("{name}: parsing and validating disagree"));
+        }
+
+        match (&name[..2], validated) {
+            ("y_", Err(e)) => failures.push(format!("{name}: rejected with {e}")),
+            ("n_", Ok(())) => failures.push(format!("{name}: accepted")),
+            _ => {}
+        }
+    }
+
+    assert!(cases > 0, "no test cases found");
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn values() {
+    let input = fs::read_to_string(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/json/test_parsing/y_string_accepted_surrogate_pairs.json"),
+    )
+    .unwrap();
+    let before = allocations();
+    let value = ParseHelper::new_char_oriented(input.as_str())
+        .parse_json()
+        .unwrap();
+    assert!(allocations() > before);
+
+    assert_eq!(value.as_array().unwrap()[0].as_str(), Some("😹💍"));
+}